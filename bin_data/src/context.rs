@@ -8,19 +8,29 @@
 //!     - [`NoArgs`]: no argument at all, or `Args = ()`.
 //!     - [`VecArgs`] and [`VecArgsBuilder`]: arguments for [`Vec`], [`slice`]s, etc.
 //!     - [`StrArgs`] and [`StrArgsBuilder`]: arguments for [`String`], [`str`], etc.
+//!       The text [`Encoding`] is given by `encoding`, for both decoding and encoding.
+//!     - [`Utf16StrArgs`] and [`Utf16StrArgsBuilder`]: arguments for UTF-16 strings.
+//!     - [`NullTerminatedArgs`] and [`NullTerminatedArgsBuilder`]: arguments for NUL-terminated
+//!       strings.
+//!     - [`FixedStrArgs`] and [`FixedStrArgsBuilder`]: arguments for fixed-width strings.
+//!     - [`BoolArgs`] and [`BoolArgsBuilder`]: arguments for [`bool`].
+//!     - [`TupleArgs`] and [`TupleArgsBuilder`]: arguments for tuples.
+//!     - [`OptionArgs`] and [`OptionArgsBuilder`]: arguments for [`Option`].
+//!     - [`FlagsArgs`] and [`FlagsArgsBuilder`]: arguments for [`flags!`](crate::flags) types.
 //!
 //! Types in this module might appear in error messages, here is an overview:
 //! - **expected enum [`Endian`], found struct [`NoEndian`]**: endianness for one of the fields
-//!     must be specified. Add the following attribute: `#[bin_data(endian = "...")]`, either to
-//!     the whole `struct`, or to individual fields.
+//!   must be specified. Add the following attribute: `#[bin_data(endian = "...")]`, either to
+//!   the whole `struct`, or to individual fields.
 //! - **expected struct [`NoEndian`], found enum [`Endian`]**: opposite situation to the previous
-//!     one, one of the fields require no runtime-specified endianness, but endianness is specified
-//!     explicitly using `#[bin_data(endian = "...")]`. Remove that superfluous attribute.
+//!   one, one of the fields require no runtime-specified endianness, but endianness is specified
+//!   explicitly using `#[bin_data(endian = "...")]`. Remove that superfluous attribute.
 //! - **_some argument builder_ does not implement [`ArgsBuilderFinished`]**: some [`Required`]
-//!     argument is not specified. Specify it in `#[bin_data(args { ... })]`. The arguments should
-//!     be given as `name = value`, separated and optionally ended by commas. Under the hood, it
-//!     calls the method `name` with argument `value` on the argument builder. For instance, the
-//!     following code set the expected length of a [`Vec`] by calling [`VecArgsBuilder::count`]:
+//!   argument is not specified. Specify it in `#[bin_data(args { ... })]`. The arguments should
+//!   be given as `name = value` (or `name` alone for methods without an argument), separated and
+//!   optionally ended by commas. Under the hood, it calls the method `name` with argument `value`
+//!   on the argument builder. For instance, the following code sets the expected length of a
+//!   [`Vec`] by calling [`VecArgsBuilder::count`]:
 //!     ```
 //!     # bin_data_macros::bin_data! {
 //!     #     #[bin_data(endian = "inherit")]
@@ -135,9 +145,13 @@ pub struct VecArgs<Args> {
 /// assert_eq!(vec![5, 6, 7], get_args(builder().args([1, 2, 3]).map_arg(|x| x + 4).finish()));
 /// ```
 ///
+/// Without a known count, use [`until_eof`] to decode elements until the end of the input stream,
+/// e.g., `#[bin_data(args:decode { until_eof })]` for a trailing list of records.
+///
 /// # Note
 /// The argument [`arg`] must be specified after [`count`].
 ///
+/// [`until_eof`]: VecArgsBuilder::until_eof
 /// [`count`]: VecArgsBuilder::count
 /// [`arg`]: VecArgsBuilder::arg
 /// [`args`]: VecArgsBuilder::args
@@ -162,11 +176,29 @@ impl<Args> VecArgsBuilder<Args> {
 
 impl VecArgsBuilder<Required> {
     /// Specify the expected number of elements in the [`Vec`].
-    pub fn count(self, n: usize) -> VecArgsBuilder<Provided<impl Iterator<Item = ()>>> {
-        VecArgsBuilder { element_args: Provided(std::iter::repeat(()).take(n)) }
+    pub fn count(self, n: usize) -> VecArgsBuilder<Provided<impl ExactSizeIterator<Item = ()>>> {
+        VecArgsBuilder { element_args: Provided(std::iter::repeat_n((), n)) }
+    }
+
+    /// Decode elements until the input stream is exhausted, instead of a fixed number of them.
+    pub fn until_eof(self) -> VecArgsBuilder<Provided<UntilEof<std::iter::Repeat<()>>>> {
+        VecArgsBuilder { element_args: Provided(UntilEof(std::iter::repeat(()))) }
+    }
+}
+
+impl<Args: Iterator> VecArgsBuilder<Provided<Args>> {
+    /// Decode elements until the input stream is exhausted, or until the arguments run out.
+    pub fn until_eof(self) -> VecArgsBuilder<Provided<UntilEof<Args>>> {
+        VecArgsBuilder { element_args: Provided(UntilEof(self.element_args.0)) }
     }
 }
 
+/// Arguments for decoding elements until the input stream is exhausted.
+///
+/// Decoding stops cleanly only if the end of stream is at the boundary between elements.
+#[derive(Debug, Copy, Clone)]
+pub struct UntilEof<Args>(pub Args);
+
 impl<Args> VecArgsBuilder<Provided<Args>> {
     /// Specify a shared argument for decoding all the elements in the [`Vec`].
     pub fn arg<A>(self, arg: A) -> VecArgsBuilder<Provided<impl Iterator<Item = A>>>
@@ -188,35 +220,432 @@ impl<Args> ArgsBuilderFinished for VecArgsBuilder<Provided<Args>> {
     }
 }
 
+/// Text encoding for a [`str`], [`String`], etc.
+///
+/// Besides UTF-8, the single-byte encodings are supported without extra dependencies. Encoding
+/// fails for characters not representable in the chosen encoding.
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Encoding {
+    /// UTF-8, the default.
+    #[default]
+    Utf8,
+    /// ISO-8859-1, where every byte is the Unicode code point of the same value.
+    Latin1,
+    /// Windows code page 1252, a superset of printable Latin-1 characters.
+    ///
+    /// The five bytes undefined in this code page are mapped to the control characters of the
+    /// same value, as in the WHATWG Encoding Standard.
+    Windows1252,
+    /// Strict 7-bit ASCII, bytes from `0x80` are invalid.
+    Ascii,
+}
+
 /// Arguments for encoding or decoding a [`str`], [`String`], etc.
 #[derive(Debug, Copy, Clone)]
 pub struct StrArgs {
     /// Number of bytes in this string.
     pub count: usize,
+    /// Text encoding of this string.
+    pub encoding: Encoding,
 }
 
 /// Named arguments builder for [`StrArgs`].
 ///
-/// This builder is relatively simple, use [`count`] to specify the length of the string.
+/// This builder is relatively simple, use [`count`] to specify the length of the string, and
+/// optionally [`encoding`] for the text encoding. Only the [`encoding`] is used for encoding, and
+/// the builder finishes as an [`Encoding`] in that case.
 /// ```
-/// # use bin_data::context::{Required, StrArgs, StrArgsBuilder, ArgsBuilderFinished};
+/// # use bin_data::context::{Encoding, Required, StrArgs, StrArgsBuilder, ArgsBuilderFinished};
 /// assert_eq!(StrArgsBuilder::<Required>::default().count(42).finish().count, 42);
+/// let args = StrArgsBuilder::<Required>::default().count(42).encoding(Encoding::Latin1).finish();
+/// assert_eq!(args.encoding, Encoding::Latin1);
+/// assert_eq!(StrArgsBuilder::<()>::default().finish(), Encoding::Utf8);
 /// ```
 ///
 /// [`count`]: StrArgsBuilder::count
+/// [`encoding`]: StrArgsBuilder::encoding
 #[derive(Default, Debug, Copy, Clone)]
 pub struct StrArgsBuilder<N> {
     count: N,
+    encoding: Encoding,
 }
 
 impl StrArgsBuilder<Required> {
     /// Specify the expected number of bytes in the string.
     pub fn count(self, n: usize) -> StrArgsBuilder<Provided<usize>> {
-        StrArgsBuilder { count: Provided(n) }
+        StrArgsBuilder { count: Provided(n), encoding: self.encoding }
+    }
+}
+
+impl<N> StrArgsBuilder<N> {
+    /// Specify the text encoding of the string.
+    pub fn encoding(self, encoding: Encoding) -> Self {
+        StrArgsBuilder { encoding, ..self }
     }
 }
 
 impl ArgsBuilderFinished for StrArgsBuilder<Provided<usize>> {
     type Output = StrArgs;
-    fn finish(self) -> StrArgs { StrArgs { count: self.count.0 } }
+    fn finish(self) -> StrArgs { StrArgs { count: self.count.0, encoding: self.encoding } }
+}
+
+impl ArgsBuilderFinished for StrArgsBuilder<()> {
+    type Output = Encoding;
+    fn finish(self) -> Encoding { self.encoding }
+}
+
+/// Arguments for decoding a [`Utf16String`](crate::data::Utf16String).
+#[derive(Debug, Copy, Clone)]
+pub struct Utf16StrArgs {
+    /// Number of code units in this string.
+    pub count: usize,
+}
+
+/// Named arguments builder for [`Utf16StrArgs`].
+///
+/// Use [`count`] to specify the length of the string in code units.
+/// ```
+/// # use bin_data::context::{Required, Utf16StrArgsBuilder, ArgsBuilderFinished};
+/// assert_eq!(Utf16StrArgsBuilder::<Required>::default().count(42).finish().count, 42);
+/// ```
+///
+/// [`count`]: Utf16StrArgsBuilder::count
+#[derive(Default, Debug, Copy, Clone)]
+pub struct Utf16StrArgsBuilder<N> {
+    count: N,
+}
+
+impl Utf16StrArgsBuilder<Required> {
+    /// Specify the expected number of code units in the string.
+    pub fn count(self, n: usize) -> Utf16StrArgsBuilder<Provided<usize>> {
+        Utf16StrArgsBuilder { count: Provided(n) }
+    }
+}
+
+impl ArgsBuilderFinished for Utf16StrArgsBuilder<Provided<usize>> {
+    type Output = Utf16StrArgs;
+    fn finish(self) -> Utf16StrArgs { Utf16StrArgs { count: self.count.0 } }
+}
+
+/// Arguments for encoding or decoding a NUL-terminated string.
+#[derive(Default, Debug, Copy, Clone)]
+pub struct NullTerminatedArgs {
+    /// Maximum number of bytes before the terminator, unlimited if `None`.
+    pub max_length: Option<usize>,
+}
+
+/// Named arguments builder for [`NullTerminatedArgs`].
+///
+/// All arguments are optional, use [`max_length`] to limit the length of the string.
+/// ```
+/// # use bin_data::context::{NullTerminatedArgsBuilder, ArgsBuilderFinished};
+/// assert_eq!(NullTerminatedArgsBuilder::default().finish().max_length, None);
+/// assert_eq!(NullTerminatedArgsBuilder::default().max_length(42).finish().max_length, Some(42));
+/// ```
+///
+/// [`max_length`]: NullTerminatedArgsBuilder::max_length
+#[derive(Default, Debug, Copy, Clone)]
+pub struct NullTerminatedArgsBuilder {
+    max_length: Option<usize>,
+}
+
+impl NullTerminatedArgsBuilder {
+    /// Specify the maximum number of bytes before the terminator.
+    pub fn max_length(self, n: usize) -> Self {
+        NullTerminatedArgsBuilder { max_length: Some(n) }
+    }
+}
+
+impl ArgsBuilderFinished for NullTerminatedArgsBuilder {
+    type Output = NullTerminatedArgs;
+    fn finish(self) -> NullTerminatedArgs { NullTerminatedArgs { max_length: self.max_length } }
+}
+
+/// Arguments for encoding or decoding a fixed-width string.
+#[derive(Default, Debug, Copy, Clone)]
+pub struct FixedStrArgs {
+    /// Byte used for padding the string to its full width.
+    pub padding: u8,
+}
+
+/// Named arguments builder for [`FixedStrArgs`].
+///
+/// All arguments are optional, use [`padding`] to pad with something other than zeros.
+/// ```
+/// # use bin_data::context::{FixedStrArgsBuilder, ArgsBuilderFinished};
+/// assert_eq!(FixedStrArgsBuilder::default().finish().padding, 0);
+/// assert_eq!(FixedStrArgsBuilder::default().padding(b' ').finish().padding, b' ');
+/// ```
+///
+/// [`padding`]: FixedStrArgsBuilder::padding
+#[derive(Default, Debug, Copy, Clone)]
+pub struct FixedStrArgsBuilder {
+    padding: u8,
+}
+
+impl FixedStrArgsBuilder {
+    /// Specify the byte used for padding.
+    pub fn padding(self, padding: u8) -> Self {
+        FixedStrArgsBuilder { padding }
+    }
+}
+
+impl ArgsBuilderFinished for FixedStrArgsBuilder {
+    type Output = FixedStrArgs;
+    fn finish(self) -> FixedStrArgs { FixedStrArgs { padding: self.padding } }
+}
+
+/// Width of the integer representing a [`bool`].
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum BoolWidth {
+    /// One byte, the default.
+    #[default]
+    U8,
+    /// Two bytes, in the given endianness.
+    U16,
+    /// Four bytes, in the given endianness.
+    U32,
+}
+
+/// Arguments for encoding or decoding a [`bool`].
+#[derive(Debug, Copy, Clone)]
+pub struct BoolArgs {
+    /// Width of the integer representation.
+    pub width: BoolWidth,
+    /// Only accept `0` and `1` when decoding, otherwise any non-zero value is `true`.
+    pub strict: bool,
+}
+
+impl Default for BoolArgs {
+    fn default() -> Self { BoolArgs { width: BoolWidth::U8, strict: true } }
+}
+
+/// Named arguments builder for [`BoolArgs`].
+///
+/// All arguments are optional. By default, a [`bool`] is a single byte, either `0` or `1`. Use
+/// [`width`] for a wider representation, and [`strict`] to accept any non-zero value as `true`.
+/// ```
+/// # use bin_data::context::{BoolArgsBuilder, BoolWidth, ArgsBuilderFinished};
+/// let args = BoolArgsBuilder::default().finish();
+/// assert_eq!((args.width, args.strict), (BoolWidth::U8, true));
+/// let args = BoolArgsBuilder::default().width(BoolWidth::U32).strict(false).finish();
+/// assert_eq!((args.width, args.strict), (BoolWidth::U32, false));
+/// ```
+///
+/// [`width`]: BoolArgsBuilder::width
+/// [`strict`]: BoolArgsBuilder::strict
+#[derive(Default, Debug, Copy, Clone)]
+pub struct BoolArgsBuilder {
+    args: BoolArgs,
+}
+
+impl BoolArgsBuilder {
+    /// Specify the width of the integer representation.
+    pub fn width(self, width: BoolWidth) -> Self {
+        BoolArgsBuilder { args: BoolArgs { width, ..self.args } }
+    }
+
+    /// Specify whether values other than `0` and `1` are rejected when decoding.
+    pub fn strict(self, strict: bool) -> Self {
+        BoolArgsBuilder { args: BoolArgs { strict, ..self.args } }
+    }
+}
+
+impl ArgsBuilderFinished for BoolArgsBuilder {
+    type Output = BoolArgs;
+    fn finish(self) -> BoolArgs { self.args }
+}
+
+/// Arguments for encoding or decoding a tuple.
+#[derive(Debug, Copy, Clone)]
+pub struct TupleArgs<Args> {
+    /// Tuple of the arguments for each element.
+    pub element_args: Args,
+}
+
+/// Named arguments builder for [`TupleArgs`].
+///
+/// If no element requires any argument, the builder finishes as `()`. Otherwise, use [`args`] to
+/// specify a tuple of arguments, one for each element in the tuple.
+/// ```
+/// # use bin_data::context::{TupleArgsBuilder, ArgsBuilderFinished};
+/// fn builder() -> TupleArgsBuilder { TupleArgsBuilder::default() }
+/// assert_eq!(builder().finish(), ());
+/// assert_eq!(builder().args((1, "two")).finish().element_args, (1, "two"));
+/// ```
+///
+/// [`args`]: TupleArgsBuilder::args
+#[derive(Default, Debug, Copy, Clone)]
+pub struct TupleArgsBuilder<Args = ()> {
+    element_args: Args,
+}
+
+impl TupleArgsBuilder {
+    /// Specify the arguments for each element in the tuple.
+    pub fn args<Args>(self, args: Args) -> TupleArgsBuilder<Provided<Args>> {
+        TupleArgsBuilder { element_args: Provided(args) }
+    }
+}
+
+impl ArgsBuilderFinished for TupleArgsBuilder {
+    type Output = ();
+    fn finish(self) {}
+}
+
+impl<Args> ArgsBuilderFinished for TupleArgsBuilder<Provided<Args>> {
+    type Output = TupleArgs<Args>;
+    fn finish(self) -> TupleArgs<Args> { TupleArgs { element_args: self.element_args.0 } }
+}
+
+/// Arguments for encoding or decoding an [`Option`].
+#[derive(Debug, Copy, Clone)]
+pub struct OptionArgs<P, Args> {
+    /// How the presence of the value is determined, see [`OptionArgsBuilder`].
+    pub presence: P,
+    /// Arguments for the value, if present.
+    pub element_args: Args,
+}
+
+/// A presence byte before the value, `0` for [`None`], and `1` for [`Some`].
+#[derive(Debug, Copy, Clone)]
+pub struct PresenceFlag {
+    /// Only accept `0` and `1` when decoding, otherwise any non-zero value means [`Some`].
+    pub strict: bool,
+}
+
+/// The presence byte is encoded and decoded as a [`bool`].
+impl From<PresenceFlag> for BoolArgs {
+    fn from(flag: PresenceFlag) -> Self {
+        BoolArgs { strict: flag.strict, ..BoolArgs::default() }
+    }
+}
+
+/// A special value representing [`None`].
+#[derive(Debug, Copy, Clone)]
+pub struct Sentinel<T>(pub T);
+
+/// Named arguments builder for [`OptionArgs`].
+///
+/// The presence scheme is required, specified by one of the following:
+/// - [`flag`] or [`strict_flag`]: a [`PresenceFlag`] byte precedes the value.
+/// - [`sentinel`]: the value is always there, and a [`Sentinel`] value means [`None`].
+/// - [`present`]: the presence is already known, e.g., from a previous field. Encoding fails if
+///   [`Some`]/[`None`] disagrees with it.
+///
+/// Arguments for the value default to those of the value type, use [`arg`] to override them.
+/// ```
+/// # use bin_data::context::{Context, ArgsBuilderFinished};
+/// # use bin_data::stream::dir;
+/// fn builder() -> <Option<u32> as Context<dir::Read>>::ArgsBuilder {
+///     <Option<u32> as Context<dir::Read>>::args_builder()
+/// }
+/// assert!(!builder().flag().finish().presence.strict);
+/// assert!(builder().strict_flag().finish().presence.strict);
+/// assert_eq!(builder().sentinel(u32::MAX).finish().presence.0, u32::MAX);
+/// assert!(!builder().present(false).finish().presence);
+/// ```
+///
+/// [`flag`]: OptionArgsBuilder::flag
+/// [`strict_flag`]: OptionArgsBuilder::strict_flag
+/// [`sentinel`]: OptionArgsBuilder::sentinel
+/// [`present`]: OptionArgsBuilder::present
+/// [`arg`]: OptionArgsBuilder::arg
+#[derive(Debug, Copy, Clone)]
+pub struct OptionArgsBuilder<P, Args> {
+    presence: P,
+    element_args: Args,
+}
+
+impl<Args> OptionArgsBuilder<Required, Args> {
+    pub(crate) fn new(element_args: Args) -> Self {
+        OptionArgsBuilder { presence: Required, element_args }
+    }
+
+    /// Use a presence byte, accepting any non-zero value for [`Some`].
+    pub fn flag(self) -> OptionArgsBuilder<Provided<PresenceFlag>, Args> {
+        OptionArgsBuilder { presence: Provided(PresenceFlag { strict: false }), element_args: self.element_args }
+    }
+
+    /// Use a presence byte, only accepting `0` and `1`.
+    pub fn strict_flag(self) -> OptionArgsBuilder<Provided<PresenceFlag>, Args> {
+        OptionArgsBuilder { presence: Provided(PresenceFlag { strict: true }), element_args: self.element_args }
+    }
+
+    /// Use a sentinel value for [`None`].
+    pub fn sentinel<T>(self, value: T) -> OptionArgsBuilder<Provided<Sentinel<T>>, Args> {
+        OptionArgsBuilder { presence: Provided(Sentinel(value)), element_args: self.element_args }
+    }
+
+    /// Specify whether the value is present.
+    pub fn present(self, present: bool) -> OptionArgsBuilder<Provided<bool>, Args> {
+        OptionArgsBuilder { presence: Provided(present), element_args: self.element_args }
+    }
+}
+
+impl<P, Args> OptionArgsBuilder<P, Args> {
+    /// Specify the arguments for the value.
+    pub fn arg<A>(self, arg: A) -> OptionArgsBuilder<P, Provided<A>> {
+        OptionArgsBuilder { presence: self.presence, element_args: Provided(arg) }
+    }
+}
+
+impl<P, Args: ArgsBuilderFinished> ArgsBuilderFinished for OptionArgsBuilder<Provided<P>, Args> {
+    type Output = OptionArgs<P, Args::Output>;
+    fn finish(self) -> Self::Output {
+        OptionArgs { presence: self.presence.0, element_args: self.element_args.finish() }
+    }
+}
+
+impl<P, A> ArgsBuilderFinished for OptionArgsBuilder<Provided<P>, Provided<A>> {
+    type Output = OptionArgs<P, A>;
+    fn finish(self) -> Self::Output {
+        OptionArgs { presence: self.presence.0, element_args: self.element_args.0 }
+    }
+}
+
+/// What to do with bits not declared in a [`flags!`](crate::flags) type.
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum UnknownBits {
+    /// Fail with `InvalidData` when decoding or encoding, the default.
+    #[default]
+    Reject,
+    /// Keep the undefined bits, so that they are written back verbatim.
+    Preserve,
+    /// Silently clear the undefined bits.
+    Drop,
+}
+
+/// Arguments for encoding or decoding a [`flags!`](crate::flags) type.
+#[derive(Default, Debug, Copy, Clone)]
+pub struct FlagsArgs {
+    /// Policy for bits not declared in the type.
+    pub unknown_bits: UnknownBits,
+}
+
+/// Named arguments builder for [`FlagsArgs`].
+///
+/// All arguments are optional, use [`unknown_bits`] to accept undefined bits.
+/// ```
+/// # use bin_data::context::{FlagsArgsBuilder, UnknownBits, ArgsBuilderFinished};
+/// assert_eq!(FlagsArgsBuilder::default().finish().unknown_bits, UnknownBits::Reject);
+/// let args = FlagsArgsBuilder::default().unknown_bits(UnknownBits::Preserve).finish();
+/// assert_eq!(args.unknown_bits, UnknownBits::Preserve);
+/// ```
+///
+/// [`unknown_bits`]: FlagsArgsBuilder::unknown_bits
+#[derive(Default, Debug, Copy, Clone)]
+pub struct FlagsArgsBuilder {
+    unknown_bits: UnknownBits,
+}
+
+impl FlagsArgsBuilder {
+    /// Specify the policy for bits not declared in the type.
+    pub fn unknown_bits(self, unknown_bits: UnknownBits) -> Self {
+        FlagsArgsBuilder { unknown_bits }
+    }
+}
+
+impl ArgsBuilderFinished for FlagsArgsBuilder {
+    type Output = FlagsArgs;
+    fn finish(self) -> FlagsArgs { FlagsArgs { unknown_bits: self.unknown_bits } }
 }
@@ -1,18 +1,92 @@
 //! Interface for encoding and decoding binary data.
 
+use std::ffi::CString;
 use std::io::{Read, Write};
+use std::marker::PhantomData;
 use std::ops::Deref;
-use crate::context::{ArgsBuilderFinished, Endian, Context, Provided, Required, NoArgs, VecArgs, VecArgsBuilder, NoEndian, StrArgs, StrArgsBuilder};
-use crate::stream::{dir, DecodeError, Direction, EncodeError};
+use crate::context::{ArgsBuilderFinished, Endian, Context, Provided, Required, NoArgs, VecArgs, VecArgsBuilder, UntilEof, NoEndian, Encoding, StrArgs, StrArgsBuilder, Utf16StrArgs, Utf16StrArgsBuilder, NullTerminatedArgs, NullTerminatedArgsBuilder, FixedStrArgs, FixedStrArgsBuilder, BoolArgs, BoolArgsBuilder, BoolWidth, TupleArgs, TupleArgsBuilder, OptionArgs, OptionArgsBuilder, PresenceFlag, Sentinel};
+use crate::stream::{dir, DecodeError, Direction, EncodeError, PositionTracker};
 
 /// Decode binary data to structured in-memory representation.
 pub trait Decode<Args = ()>: Context<dir::Read> + Sized {
     /// Decode an instance of `Self` from input stream with the given arguments.
     fn decode_with<R: Read + ?Sized>(reader: &mut R, endian: Self::EndianContext, args: Args) -> Result<Self, DecodeError>;
+    /// Decode an instance of `Self` from a position-tracked input stream with the given arguments.
+    ///
+    /// This only differs from [`decode_with`] for data whose layout depends on the position (e.g.,
+    /// using `@align`), directly or through its elements. By default, the layout is assumed not to
+    /// depend on the position. Containers pass the tracked stream on to their elements.
+    ///
+    /// [`decode_with`]: Decode::decode_with
+    fn decode_tracked<R: Read + ?Sized>(reader: &mut PositionTracker<R>, endian: Self::EndianContext, args: Args) -> Result<Self, DecodeError> {
+        Self::decode_with(reader, endian, args)
+    }
     /// Decode an instance of `Self` from input stream with default arguments.
+    ///
+    /// Errors are annotated with the byte offset at which decoding failed.
     fn decode<R: Read + ?Sized>(reader: &mut R) -> Result<Self, DecodeError>
         where Self::EndianContext: Default, Self::ArgsBuilder: ArgsBuilderFinished<Output = Args> {
-        Self::decode_with(reader, Self::EndianContext::default(), Self::args_builder().finish())
+        let mut reader = PositionTracker::new(reader);
+        Self::decode_tracked(&mut reader, Self::EndianContext::default(), Self::args_builder().finish())
+            .map_err(|err| err.at_offset(reader.last_read_position()))
+    }
+}
+
+/// Decode binary data from an in-memory byte slice, possibly borrowing from it.
+///
+/// This is implemented for the [`Decode`] types in this crate and those declared with `bin_data`,
+/// and additionally for `&'de [u8]`, `&'de str`, and [`UnalignedSlice<'de, T>`], which borrow
+/// from the input instead of allocating. Containers such as [`Vec`], [`Option`], arrays, and
+/// tuples implement this trait whenever their elements do, so they may contain borrowed data.
+/// Types with a lifetime parameter declared with `bin_data` implement this trait instead of
+/// [`Decode`]:
+/// ```
+/// # use bin_data::data::{DecodeBorrowed, UnalignedSlice};
+/// # use bin_data_macros::bin_data;
+/// bin_data! {
+///     #[bin_data(endian = "little")]
+///     pub struct Sample<'a> {
+///         #[bin_data(encode = name.len() as u8)]
+///         let name_length: u8,
+///         #[bin_data(args:decode { count = name_length as usize })]
+///         pub name: &'a str,
+///         #[bin_data(args:decode { count = 2 })]
+///         pub values: UnalignedSlice<'a, u16>,
+///     }
+/// }
+/// let input = b"\x03abc\x01\x00\x02\x00";
+/// let sample = Sample::decode_borrowed(&mut input.as_ref()).unwrap();
+/// assert_eq!(sample.name, "abc");
+/// assert_eq!(sample.values.iter().collect::<Vec<_>>(), [1, 2]);
+/// ```
+///
+/// For a hand-written [`Decode`] type, implement this trait by forwarding to
+/// [`Decode::decode_tracked`].
+pub trait DecodeBorrowed<'de, Args = ()>: Context<dir::Read> + Sized {
+    /// Decode an instance of `Self` from an input slice with the given arguments.
+    fn decode_borrowed_with(reader: &mut PositionTracker<&'de [u8]>, endian: Self::EndianContext, args: Args) -> Result<Self, DecodeError>;
+    /// Decode an instance of `Self` from an input slice with default arguments, and advance the
+    /// slice past the decoded bytes.
+    ///
+    /// Errors are annotated with the byte offset at which decoding failed.
+    fn decode_borrowed(input: &mut &'de [u8]) -> Result<Self, DecodeError>
+        where Self::EndianContext: Default, Self::ArgsBuilder: ArgsBuilderFinished<Output = Args> {
+        let mut reader = PositionTracker::new(*input);
+        let result = Self::decode_borrowed_with(&mut reader, Self::EndianContext::default(), Self::args_builder().finish())
+            .map_err(|err| err.at_offset(reader.last_read_position()));
+        *input = reader.into_inner();
+        result
+    }
+}
+
+/// Data never borrowing from the input is decoded from slices as usual.
+macro_rules! decode_borrowed_by_decode {
+    ($t:ty: $args:ty $(; $($generics:tt)*)?) => {
+        impl<'de, $($($generics)*)?> DecodeBorrowed<'de, $args> for $t {
+            fn decode_borrowed_with(reader: &mut PositionTracker<&'de [u8]>, endian: Self::EndianContext, args: $args) -> Result<Self, DecodeError> {
+                Self::decode_tracked(reader, endian, args)
+            }
+        }
     }
 }
 
@@ -20,23 +94,68 @@ pub trait Decode<Args = ()>: Context<dir::Read> + Sized {
 pub trait Encode<Args = ()>: Context<dir::Write> {
     /// Encode `self` to the output stream with the given arguments.
     fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, endian: Self::EndianContext, args: Args) -> Result<(), EncodeError>;
+    /// Encode `self` to a position-tracked output stream with the given arguments.
+    ///
+    /// This only differs from [`encode_with`] for data whose layout depends on the position, see
+    /// [`Decode::decode_tracked`]. By default, this forwards to [`encode_with`].
+    ///
+    /// [`encode_with`]: Encode::encode_with
+    fn encode_tracked<W: Write + ?Sized>(&self, writer: &mut PositionTracker<W>, endian: Self::EndianContext, args: Args) -> Result<(), EncodeError> {
+        self.encode_with(writer, endian, args)
+    }
     /// Encode `self` to the output stream with default arguments.
     fn encode<W: Write + ?Sized>(&self, writer: &mut W) -> Result<(), EncodeError>
         where Self::EndianContext: Default, Self::ArgsBuilder: ArgsBuilderFinished<Output = Args> {
-        self.encode_with(writer, Self::EndianContext::default(), Self::args_builder().finish())
+        let mut writer = PositionTracker::new(writer);
+        self.encode_tracked(&mut writer, Self::EndianContext::default(), Self::args_builder().finish())
+    }
+    /// Number of bytes `self` is encoded to, with the given arguments.
+    ///
+    /// By default, `self` is encoded to a [`PositionTracker::counter`], which discards the bytes.
+    /// Data with a simple layout overrides this to calculate the size without formatting bytes.
+    fn encoded_size_with(&self, endian: Self::EndianContext, args: Args) -> Result<u64, EncodeError> {
+        let mut counter = PositionTracker::counter();
+        self.encode_tracked(&mut counter, endian, args)?;
+        Ok(counter.position())
+    }
+    /// Number of bytes `self` is encoded to, with the given arguments, when encoding starts at
+    /// `position` in the output stream.
+    ///
+    /// This only differs from [`encoded_size_with`] for data whose layout depends on the position
+    /// (e.g., using `@align`), directly or through its elements. By default, the layout is assumed
+    /// not to depend on the position. Containers pass the position on to their elements.
+    ///
+    /// [`encoded_size_with`]: Encode::encoded_size_with
+    fn encoded_size_at(&self, position: u64, endian: Self::EndianContext, args: Args) -> Result<u64, EncodeError> {
+        let _ = position;
+        self.encoded_size_with(endian, args)
+    }
+    /// Number of bytes `self` is encoded to, with default arguments.
+    fn encoded_size(&self) -> Result<u64, EncodeError>
+        where Self::EndianContext: Default, Self::ArgsBuilder: ArgsBuilderFinished<Output = Args> {
+        self.encoded_size_with(Self::EndianContext::default(), Self::args_builder().finish())
     }
 }
 
-impl<'a, T: Context<dir::Write> + ?Sized> Context<dir::Write> for &'a T {
+impl<T: Context<dir::Write> + ?Sized> Context<dir::Write> for &T {
     type EndianContext = T::EndianContext;
     type ArgsBuilder = T::ArgsBuilder;
     fn args_builder() -> Self::ArgsBuilder { T::args_builder() }
 }
 
-impl<'a, Args, T: Encode<Args> + ?Sized> Encode<Args> for &'a T {
+impl<Args, T: Encode<Args> + ?Sized> Encode<Args> for &T {
     fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, endian: Self::EndianContext, args: Args) -> Result<(), EncodeError> {
         T::encode_with(self, writer, endian, args)
     }
+    fn encode_tracked<W: Write + ?Sized>(&self, writer: &mut PositionTracker<W>, endian: Self::EndianContext, args: Args) -> Result<(), EncodeError> {
+        T::encode_tracked(self, writer, endian, args)
+    }
+    fn encoded_size_with(&self, endian: Self::EndianContext, args: Args) -> Result<u64, EncodeError> {
+        T::encoded_size_with(self, endian, args)
+    }
+    fn encoded_size_at(&self, position: u64, endian: Self::EndianContext, args: Args) -> Result<u64, EncodeError> {
+        T::encoded_size_at(self, position, endian, args)
+    }
 }
 
 impl<T: Context<dir::Write> + ?Sized> Context<dir::Write> for Box<T> {
@@ -49,17 +168,38 @@ impl<Args, T: Encode<Args> + ?Sized> Encode<Args> for Box<T> {
     fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, endian: Self::EndianContext, args: Args) -> Result<(), EncodeError> {
         T::encode_with(self, writer, endian, args)
     }
+    fn encode_tracked<W: Write + ?Sized>(&self, writer: &mut PositionTracker<W>, endian: Self::EndianContext, args: Args) -> Result<(), EncodeError> {
+        T::encode_tracked(self, writer, endian, args)
+    }
+    fn encoded_size_with(&self, endian: Self::EndianContext, args: Args) -> Result<u64, EncodeError> {
+        T::encoded_size_with(self, endian, args)
+    }
+    fn encoded_size_at(&self, position: u64, endian: Self::EndianContext, args: Args) -> Result<u64, EncodeError> {
+        T::encoded_size_at(self, position, endian, args)
+    }
 }
 
-fn encode_iter<W, I, Args>(writer: &mut W, type_name: &'static str,
+fn encode_iter<W, I, Args>(writer: &mut PositionTracker<W>, type_name: &'static str,
                            endian: <I::Item as Context<dir::Write>>::EndianContext,
                            iter: I, args: Args) -> Result<(), EncodeError>
     where W: Write + ?Sized, I: IntoIterator, Args: IntoIterator, I::Item: Encode<Args::Item> {
     let mut args = args.into_iter();
-    iter.into_iter().try_for_each(|x| {
+    iter.into_iter().enumerate().try_for_each(|(index, x)| {
         let err = EncodeError::InvalidArgument(type_name, "not enough arguments");
         let arg = args.next().ok_or(err)?;
-        x.encode_with(writer, endian, arg)
+        x.encode_tracked(writer, endian, arg).map_err(|err| err.at_index(index))
+    })
+}
+
+fn encoded_size_iter<I, Args>(type_name: &'static str, position: u64,
+                              endian: <I::Item as Context<dir::Write>>::EndianContext,
+                              iter: I, args: Args) -> Result<u64, EncodeError>
+    where I: IntoIterator, Args: IntoIterator, I::Item: Encode<Args::Item> {
+    let mut args = args.into_iter();
+    iter.into_iter().enumerate().try_fold(0, |size, (index, x)| {
+        let err = EncodeError::InvalidArgument(type_name, "not enough arguments");
+        let arg = args.next().ok_or(err)?;
+        Ok(size + x.encoded_size_at(position + size, endian, arg).map_err(|err| err.at_index(index))?)
     })
 }
 
@@ -71,12 +211,15 @@ impl<Dir: Direction> Context<Dir> for () {
 
 impl Encode for () {
     fn encode_with<W: Write + ?Sized>(&self, _writer: &mut W, _: NoEndian, _: ()) -> Result<(), EncodeError> { Ok(()) }
+    fn encoded_size_with(&self, _: NoEndian, _: ()) -> Result<u64, EncodeError> { Ok(0) }
 }
 
 impl Decode for () {
     fn decode_with<R: Read + ?Sized>(_reader: &mut R, _: NoEndian, _: ()) -> Result<Self, DecodeError> { Ok(()) }
 }
 
+decode_borrowed_by_decode!((): ());
+
 /// Marker trait: `U: View<T>` indicates that when we need to encode a value of type `T`, we can
 /// encode a value of `U` instead.
 pub trait View<T: ?Sized> {}
@@ -136,8 +279,17 @@ impl<'a, A, B, P> Context<dir::Write> for SliceViewRef<'a, A, P>
 impl<'a, A, B, P, Args> Encode<VecArgs<Args>> for SliceViewRef<'a, A, P>
     where B: ?Sized + 'a, P: Fn(&A) -> &B, Args: Iterator, B: Encode<Args::Item> {
     fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<(), EncodeError> {
+        self.encode_tracked(&mut PositionTracker::new(writer), endian, args)
+    }
+    fn encode_tracked<W: Write + ?Sized>(&self, writer: &mut PositionTracker<W>, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<(), EncodeError> {
         encode_iter(writer, "SliceViewRef", endian, self, args.element_args)
     }
+    fn encoded_size_with(&self, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<u64, EncodeError> {
+        self.encoded_size_at(0, endian, args)
+    }
+    fn encoded_size_at(&self, position: u64, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<u64, EncodeError> {
+        encoded_size_iter("SliceViewRef", position, endian, self, args.element_args)
+    }
 }
 
 /// View into a slice, with every element projected using `P`.
@@ -192,13 +344,242 @@ impl<'a, A, B: Context<dir::Write>, P: Fn(&A) -> B> Context<dir::Write> for Slic
 impl<'a, A, B, P: Fn(&A) -> B, Args> Encode<VecArgs<Args>> for SliceView<'a, A, P>
     where Args: Iterator, B: Encode<Args::Item> {
     fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<(), EncodeError> {
+        self.encode_tracked(&mut PositionTracker::new(writer), endian, args)
+    }
+    fn encode_tracked<W: Write + ?Sized>(&self, writer: &mut PositionTracker<W>, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<(), EncodeError> {
         encode_iter(writer, "SliceView", endian, self, args.element_args)
     }
+    fn encoded_size_with(&self, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<u64, EncodeError> {
+        self.encoded_size_at(0, endian, args)
+    }
+    fn encoded_size_at(&self, position: u64, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<u64, EncodeError> {
+        encoded_size_iter("SliceView", position, endian, self, args.element_args)
+    }
 }
 
 /// Used in automatically generated code to aid type inference.
 pub fn assert_is_view<T: ?Sized, U: View<T>>(x: U) -> U { x }
 
+/// Tagged unions, where a tag decides which alternative is present in the binary data.
+///
+/// This is implemented for every `enum` declared with `bin_data`. When the tag is stored outside
+/// the `enum` (specified by the parent using `#[bin_data(args { tag = ... })]`), use [`tag`] to
+/// calculate the tag for encoding.
+///
+/// [`tag`]: Tagged::tag
+pub trait Tagged {
+    /// Type of the tag.
+    type Tag;
+    /// Get the tag for the alternative in `self`.
+    fn tag(&self) -> Self::Tag;
+}
+
+/// Declare a set of named bits over an integer container.
+///
+/// The generated type is a thin wrapper around the integer, with an associated constant for each
+/// flag, and the usual set operations. It is encoded as the integer in the given endianness, and
+/// the policy for undefined bits is chosen by [`FlagsArgsBuilder`]:
+/// ```
+/// # use bin_data::context::{Context, Endian, UnknownBits, ArgsBuilderFinished};
+/// # use bin_data::data::{Decode, Encode, Le};
+/// # use bin_data::stream::dir;
+/// bin_data::flags! {
+///     /// Abilities of a zombie.
+///     pub struct Abilities: u16 {
+///         const FLY = 1 << 0;
+///         const SWIM = 1 << 1;
+///         const DIG = 1 << 4;
+///     }
+/// }
+///
+/// let abilities = Le::<Abilities>::decode(&mut [0x11, 0x00].as_ref()).unwrap().0;
+/// assert_eq!(abilities, Abilities::FLY | Abilities::DIG);
+/// assert!(abilities.contains(Abilities::DIG) && !abilities.contains(Abilities::SWIM));
+/// assert_eq!(format!("{abilities:?}"), "Abilities(FLY | DIG)");
+/// assert!(Le::<Abilities>::decode(&mut [0x11, 0x80].as_ref()).is_err());
+///
+/// let args = <Abilities as Context<dir::Read>>::args_builder().unknown_bits(UnknownBits::Preserve).finish();
+/// let abilities = Abilities::decode_with(&mut [0x11, 0x80].as_ref(), Endian::Little, args).unwrap();
+/// assert_eq!(abilities.bits(), 0x8011);
+/// assert_eq!(format!("{abilities:?}"), "Abilities(FLY | DIG | 0x8000)");
+/// ```
+///
+/// [`FlagsArgsBuilder`]: crate::context::FlagsArgsBuilder
+#[macro_export]
+macro_rules! flags {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident: $repr:ty {
+            $(
+                $(#[$flag_attr:meta])*
+                const $flag:ident = $value:expr;
+            )*
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Default, Copy, Clone, Eq, PartialEq, Hash)]
+        $vis struct $name($repr);
+
+        #[allow(dead_code)]
+        impl $name {
+            $(
+                $(#[$flag_attr])*
+                pub const $flag: Self = Self($value);
+            )*
+            /// All the declared flags.
+            pub const ALL: Self = Self(0 $(| $value)*);
+
+            /// No flags at all.
+            pub const fn empty() -> Self { Self(0) }
+            /// The underlying integer.
+            pub const fn bits(self) -> $repr { self.0 }
+            /// Convert from an integer, or `None` if any undefined bit is set.
+            pub const fn from_bits(bits: $repr) -> ::std::option::Option<Self> {
+                if bits & !Self::ALL.0 == 0 { ::std::option::Option::Some(Self(bits)) } else { ::std::option::Option::None }
+            }
+            /// Convert from an integer, keeping undefined bits.
+            pub const fn from_bits_retain(bits: $repr) -> Self { Self(bits) }
+            /// Convert from an integer, clearing undefined bits.
+            pub const fn from_bits_truncate(bits: $repr) -> Self { Self(bits & Self::ALL.0) }
+            /// Whether no bit is set.
+            pub const fn is_empty(self) -> bool { self.0 == 0 }
+            /// Whether all bits in `other` are set.
+            pub const fn contains(self, other: Self) -> bool { self.0 & other.0 == other.0 }
+            /// Set all bits in `other`.
+            pub fn insert(&mut self, other: Self) { self.0 |= other.0 }
+            /// Clear all bits in `other`.
+            pub fn remove(&mut self, other: Self) { self.0 &= !other.0 }
+
+            fn check_unknown_bits(self, unknown_bits: $crate::context::UnknownBits) -> ::std::option::Option<Self> {
+                match unknown_bits {
+                    $crate::context::UnknownBits::Reject => Self::from_bits(self.0),
+                    $crate::context::UnknownBits::Preserve => ::std::option::Option::Some(self),
+                    $crate::context::UnknownBits::Drop => ::std::option::Option::Some(Self::from_bits_truncate(self.0)),
+                }
+            }
+        }
+
+        impl ::std::ops::BitOr for $name {
+            type Output = Self;
+            fn bitor(self, other: Self) -> Self { Self(self.0 | other.0) }
+        }
+
+        impl ::std::ops::BitOrAssign for $name {
+            fn bitor_assign(&mut self, other: Self) { self.0 |= other.0 }
+        }
+
+        impl ::std::ops::BitAnd for $name {
+            type Output = Self;
+            fn bitand(self, other: Self) -> Self { Self(self.0 & other.0) }
+        }
+
+        impl ::std::ops::BitAndAssign for $name {
+            fn bitand_assign(&mut self, other: Self) { self.0 &= other.0 }
+        }
+
+        impl ::std::fmt::Debug for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                let mut separator = "";
+                f.write_str(::std::concat!(::std::stringify!($name), "("))?;
+                $(
+                    if Self::$flag.0 != 0 && self.contains(Self::$flag) {
+                        ::std::write!(f, "{separator}{}", ::std::stringify!($flag))?;
+                        separator = " | ";
+                    }
+                )*
+                let unknown = self.0 & !Self::ALL.0;
+                if unknown != 0 {
+                    ::std::write!(f, "{separator}{unknown:#x}")?;
+                }
+                f.write_str(")")
+            }
+        }
+
+        impl<Dir: $crate::stream::Direction> $crate::context::Context<Dir> for $name {
+            type EndianContext = $crate::context::Endian;
+            type ArgsBuilder = $crate::context::FlagsArgsBuilder;
+            fn args_builder() -> Self::ArgsBuilder { ::std::default::Default::default() }
+        }
+
+        impl $crate::data::Decode<$crate::context::FlagsArgs> for $name {
+            fn decode_with<R: ::std::io::Read + ?Sized>(
+                reader: &mut R,
+                endian: $crate::context::Endian,
+                args: $crate::context::FlagsArgs,
+            ) -> ::std::result::Result<Self, $crate::stream::DecodeError> {
+                let bits = <$repr as $crate::data::Decode>::decode_with(reader, endian, ())?;
+                Self(bits).check_unknown_bits(args.unknown_bits)
+                    .ok_or($crate::stream::DecodeError::InvalidData(::std::stringify!($name)))
+            }
+        }
+
+        impl<'de> $crate::data::DecodeBorrowed<'de, $crate::context::FlagsArgs> for $name {
+            fn decode_borrowed_with(
+                reader: &mut $crate::stream::PositionTracker<&'de [u8]>,
+                endian: $crate::context::Endian,
+                args: $crate::context::FlagsArgs,
+            ) -> ::std::result::Result<Self, $crate::stream::DecodeError> {
+                <Self as $crate::data::Decode<$crate::context::FlagsArgs>>::decode_with(reader, endian, args)
+            }
+        }
+
+        impl $crate::data::Encode<$crate::context::FlagsArgs> for $name {
+            fn encode_with<W: ::std::io::Write + ?Sized>(
+                &self,
+                writer: &mut W,
+                endian: $crate::context::Endian,
+                args: $crate::context::FlagsArgs,
+            ) -> ::std::result::Result<(), $crate::stream::EncodeError> {
+                let flags = self.check_unknown_bits(args.unknown_bits)
+                    .ok_or($crate::stream::EncodeError::InvalidData(::std::stringify!($name)))?;
+                $crate::data::Encode::encode_with(&flags.0, writer, endian, ())
+            }
+            fn encoded_size_with(
+                &self, _: $crate::context::Endian, _: $crate::context::FlagsArgs,
+            ) -> ::std::result::Result<u64, $crate::stream::EncodeError> {
+                ::std::result::Result::Ok(::std::mem::size_of::<$repr>() as u64)
+            }
+        }
+
+        impl $crate::data::FixedSize for $name {
+            const ENCODED_SIZE: usize = ::std::mem::size_of::<$repr>();
+        }
+    };
+}
+
+/// Data with a statically known encoded size, in bytes.
+///
+/// This is implemented by `bin_data` for every `struct` consisting only of fixed-size fields and
+/// `@magic`, `@pad`, or `@assert` directives, so that the size is available in const contexts:
+/// ```
+/// # use bin_data::data::{Encode, FixedSize};
+/// # use bin_data_macros::bin_data;
+/// bin_data! {
+///     #[bin_data(endian = "little")]
+///     pub struct Record {
+///         @magic(*b"REC"),
+///         pub id: u32,
+///         @pad(2),
+///         pub value: u16,
+///     }
+/// }
+/// let mut buffer = [0; Record::ENCODED_SIZE];
+/// Record { id: 1, value: 2 }.encode(&mut buffer.as_mut_slice()).unwrap();
+/// assert_eq!(buffer, *b"REC\x01\0\0\0\0\0\x02\0");
+/// ```
+pub trait FixedSize {
+    /// Number of bytes in the encoded data.
+    const ENCODED_SIZE: usize;
+}
+
+impl<T: FixedSize + ?Sized> FixedSize for &T {
+    const ENCODED_SIZE: usize = T::ENCODED_SIZE;
+}
+
+impl FixedSize for () {
+    const ENCODED_SIZE: usize = 0;
+}
+
 /// Plain old data, can be directly encoded to and decoded from raw bytes.
 pub trait PlainData: Sized {
     /// Storage type for the raw bytes, typically a `[u8; N]`.
@@ -228,6 +609,10 @@ macro_rules! impl_primitive_plain_data {
                 }
             }
 
+            impl FixedSize for $t {
+                const ENCODED_SIZE: usize = std::mem::size_of::<Self>();
+            }
+
             impl<Dir: Direction> Context<Dir> for $t {
                 type EndianContext = Endian;
                 type ArgsBuilder = NoArgs;
@@ -240,10 +625,15 @@ macro_rules! impl_primitive_plain_data {
                 }
             }
 
+            decode_borrowed_by_decode!($t: ());
+
             impl Encode for $t {
                 fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, endian: Endian, _args: ()) -> Result<(), EncodeError> {
                     plain_data_encode_with(self, writer, endian)
                 }
+                fn encoded_size_with(&self, _endian: Endian, _args: ()) -> Result<u64, EncodeError> {
+                    Ok(std::mem::size_of::<Self>() as u64)
+                }
             }
         )+
     }
@@ -274,6 +664,292 @@ fn plain_data_encode_with<T: PlainData, W: Write + ?Sized>(
     writer.write_all(value.to_bytes(endian).as_ref()).map_err(EncodeError::from)
 }
 
+impl<Dir: Direction> Context<Dir> for bool {
+    type EndianContext = Endian;
+    type ArgsBuilder = BoolArgsBuilder;
+    fn args_builder() -> BoolArgsBuilder { BoolArgsBuilder::default() }
+}
+
+/// Decode a [`bool`] from an integer, see [`BoolArgsBuilder`] for the representation.
+/// ```
+/// # use bin_data::data::{Decode, Le};
+/// assert_eq!(Le::<bool>::decode(&mut [1].as_ref()).unwrap(), Le(true));
+/// assert!(Le::<bool>::decode(&mut [2].as_ref()).is_err());
+/// ```
+impl Decode<BoolArgs> for bool {
+    fn decode_with<R: Read + ?Sized>(reader: &mut R, endian: Endian, args: BoolArgs) -> Result<Self, DecodeError> {
+        let value = match args.width {
+            BoolWidth::U8 => u8::decode_with(reader, endian, ())? as u32,
+            BoolWidth::U16 => u16::decode_with(reader, endian, ())? as u32,
+            BoolWidth::U32 => u32::decode_with(reader, endian, ())?,
+        };
+        match value {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ if args.strict => Err(DecodeError::InvalidData("bool")),
+            _ => Ok(true),
+        }
+    }
+}
+
+decode_borrowed_by_decode!(bool: BoolArgs);
+
+impl Encode<BoolArgs> for bool {
+    fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, endian: Endian, args: BoolArgs) -> Result<(), EncodeError> {
+        match args.width {
+            BoolWidth::U8 => (*self as u8).encode_with(writer, endian, ()),
+            BoolWidth::U16 => (*self as u16).encode_with(writer, endian, ()),
+            BoolWidth::U32 => (*self as u32).encode_with(writer, endian, ()),
+        }
+    }
+    fn encoded_size_with(&self, _: Endian, args: BoolArgs) -> Result<u64, EncodeError> {
+        Ok(match args.width {
+            BoolWidth::U8 => 1,
+            BoolWidth::U16 => 2,
+            BoolWidth::U32 => 4,
+        })
+    }
+}
+
+/// An optional value, with the presence scheme specified by [`OptionArgsBuilder`].
+/// ```
+/// # use bin_data::context::{Context, ArgsBuilderFinished};
+/// # use bin_data::data::{Decode, Encode};
+/// # use bin_data::stream::dir;
+/// # use bin_data::context::Endian::Little;
+/// let args = <Option<u16> as Context<dir::Read>>::args_builder().sentinel(0xFFFF).finish();
+/// let value = Option::<u16>::decode_with(&mut [0xFF, 0xFF].as_ref(), Little, args);
+/// assert_eq!(value.unwrap(), None);
+///
+/// let mut buffer = Vec::new();
+/// let args = <Option<u16> as Context<dir::Write>>::args_builder().flag().finish();
+/// Some(42_u16).encode_with(&mut buffer, Little, args).unwrap();
+/// assert_eq!(buffer, [1, 42, 0]);
+/// ```
+impl<Dir: Direction, T: Context<Dir>> Context<Dir> for Option<T> {
+    type EndianContext = T::EndianContext;
+    type ArgsBuilder = OptionArgsBuilder<Required, T::ArgsBuilder>;
+    fn args_builder() -> Self::ArgsBuilder { OptionArgsBuilder::new(T::args_builder()) }
+}
+
+impl<Args, T: Decode<Args>> Decode<OptionArgs<PresenceFlag, Args>> for Option<T> {
+    fn decode_with<R: Read + ?Sized>(reader: &mut R, endian: Self::EndianContext, args: OptionArgs<PresenceFlag, Args>) -> Result<Self, DecodeError> {
+        Self::decode_tracked(&mut PositionTracker::new(reader), endian, args)
+    }
+    fn decode_tracked<R: Read + ?Sized>(reader: &mut PositionTracker<R>, endian: Self::EndianContext, args: OptionArgs<PresenceFlag, Args>) -> Result<Self, DecodeError> {
+        let present = bool::decode_with(reader, Endian::Little, args.presence.into())
+            .map_err(|err| err.in_field("Option", "flag"))?;
+        Self::decode_tracked(reader, endian, OptionArgs { presence: present, element_args: args.element_args })
+    }
+}
+
+impl<'de, Args, T: DecodeBorrowed<'de, Args>> DecodeBorrowed<'de, OptionArgs<PresenceFlag, Args>> for Option<T> {
+    fn decode_borrowed_with(reader: &mut PositionTracker<&'de [u8]>, endian: Self::EndianContext, args: OptionArgs<PresenceFlag, Args>) -> Result<Self, DecodeError> {
+        let present = bool::decode_with(reader, Endian::Little, args.presence.into())
+            .map_err(|err| err.in_field("Option", "flag"))?;
+        Self::decode_borrowed_with(reader, endian, OptionArgs { presence: present, element_args: args.element_args })
+    }
+}
+
+impl<Args, T: Decode<Args> + PartialEq> Decode<OptionArgs<Sentinel<T>, Args>> for Option<T> {
+    fn decode_with<R: Read + ?Sized>(reader: &mut R, endian: Self::EndianContext, args: OptionArgs<Sentinel<T>, Args>) -> Result<Self, DecodeError> {
+        Self::decode_tracked(&mut PositionTracker::new(reader), endian, args)
+    }
+    fn decode_tracked<R: Read + ?Sized>(reader: &mut PositionTracker<R>, endian: Self::EndianContext, args: OptionArgs<Sentinel<T>, Args>) -> Result<Self, DecodeError> {
+        let value = T::decode_tracked(reader, endian, args.element_args)?;
+        Ok(Some(value).filter(|value| *value != args.presence.0))
+    }
+}
+
+impl<'de, Args, T: DecodeBorrowed<'de, Args> + PartialEq> DecodeBorrowed<'de, OptionArgs<Sentinel<T>, Args>> for Option<T> {
+    fn decode_borrowed_with(reader: &mut PositionTracker<&'de [u8]>, endian: Self::EndianContext, args: OptionArgs<Sentinel<T>, Args>) -> Result<Self, DecodeError> {
+        let value = T::decode_borrowed_with(reader, endian, args.element_args)?;
+        Ok(Some(value).filter(|value| *value != args.presence.0))
+    }
+}
+
+impl<Args, T: Decode<Args>> Decode<OptionArgs<bool, Args>> for Option<T> {
+    fn decode_with<R: Read + ?Sized>(reader: &mut R, endian: Self::EndianContext, args: OptionArgs<bool, Args>) -> Result<Self, DecodeError> {
+        Self::decode_tracked(&mut PositionTracker::new(reader), endian, args)
+    }
+    fn decode_tracked<R: Read + ?Sized>(reader: &mut PositionTracker<R>, endian: Self::EndianContext, args: OptionArgs<bool, Args>) -> Result<Self, DecodeError> {
+        if !args.presence { return Ok(None); }
+        T::decode_tracked(reader, endian, args.element_args).map(Some)
+    }
+}
+
+impl<'de, Args, T: DecodeBorrowed<'de, Args>> DecodeBorrowed<'de, OptionArgs<bool, Args>> for Option<T> {
+    fn decode_borrowed_with(reader: &mut PositionTracker<&'de [u8]>, endian: Self::EndianContext, args: OptionArgs<bool, Args>) -> Result<Self, DecodeError> {
+        if !args.presence { return Ok(None); }
+        T::decode_borrowed_with(reader, endian, args.element_args).map(Some)
+    }
+}
+
+impl<Args, T: Encode<Args>> Encode<OptionArgs<PresenceFlag, Args>> for Option<T> {
+    fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, endian: Self::EndianContext, args: OptionArgs<PresenceFlag, Args>) -> Result<(), EncodeError> {
+        self.encode_tracked(&mut PositionTracker::new(writer), endian, args)
+    }
+    fn encode_tracked<W: Write + ?Sized>(&self, writer: &mut PositionTracker<W>, endian: Self::EndianContext, args: OptionArgs<PresenceFlag, Args>) -> Result<(), EncodeError> {
+        self.is_some().encode_with(writer, Endian::Little, args.presence.into())
+            .map_err(|err| err.in_field("Option", "flag"))?;
+        self.encode_tracked(writer, endian, OptionArgs { presence: self.is_some(), element_args: args.element_args })
+    }
+    fn encoded_size_with(&self, endian: Self::EndianContext, args: OptionArgs<PresenceFlag, Args>) -> Result<u64, EncodeError> {
+        self.encoded_size_at(0, endian, args)
+    }
+    fn encoded_size_at(&self, position: u64, endian: Self::EndianContext, args: OptionArgs<PresenceFlag, Args>) -> Result<u64, EncodeError> {
+        let size = self.is_some().encoded_size_with(Endian::Little, args.presence.into())?;
+        let args = OptionArgs { presence: self.is_some(), element_args: args.element_args };
+        Ok(size + self.encoded_size_at(position + size, endian, args)?)
+    }
+}
+
+/// Encoding fails with [`EncodeError::InvalidData`] for `Some(sentinel)`, which would decode as
+/// [`None`].
+impl<Args, T: Encode<Args> + PartialEq> Encode<OptionArgs<Sentinel<T>, Args>> for Option<T> {
+    fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, endian: Self::EndianContext, args: OptionArgs<Sentinel<T>, Args>) -> Result<(), EncodeError> {
+        sentinel_value(self, &args.presence)?.encode_with(writer, endian, args.element_args)
+    }
+    fn encode_tracked<W: Write + ?Sized>(&self, writer: &mut PositionTracker<W>, endian: Self::EndianContext, args: OptionArgs<Sentinel<T>, Args>) -> Result<(), EncodeError> {
+        sentinel_value(self, &args.presence)?.encode_tracked(writer, endian, args.element_args)
+    }
+    fn encoded_size_with(&self, endian: Self::EndianContext, args: OptionArgs<Sentinel<T>, Args>) -> Result<u64, EncodeError> {
+        sentinel_value(self, &args.presence)?.encoded_size_with(endian, args.element_args)
+    }
+    fn encoded_size_at(&self, position: u64, endian: Self::EndianContext, args: OptionArgs<Sentinel<T>, Args>) -> Result<u64, EncodeError> {
+        sentinel_value(self, &args.presence)?.encoded_size_at(position, endian, args.element_args)
+    }
+}
+
+fn sentinel_value<'a, T: PartialEq>(value: &'a Option<T>, sentinel: &'a Sentinel<T>) -> Result<&'a T, EncodeError> {
+    match value {
+        Some(value) if *value == sentinel.0 => Err(EncodeError::InvalidData("Option")),
+        Some(value) => Ok(value),
+        None => Ok(&sentinel.0),
+    }
+}
+
+impl<Args, T: Encode<Args>> Encode<OptionArgs<bool, Args>> for Option<T> {
+    fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, endian: Self::EndianContext, args: OptionArgs<bool, Args>) -> Result<(), EncodeError> {
+        self.encode_tracked(&mut PositionTracker::new(writer), endian, args)
+    }
+    fn encode_tracked<W: Write + ?Sized>(&self, writer: &mut PositionTracker<W>, endian: Self::EndianContext, args: OptionArgs<bool, Args>) -> Result<(), EncodeError> {
+        match (args.presence, self) {
+            (true, Some(value)) => value.encode_tracked(writer, endian, args.element_args),
+            (false, None) => Ok(()),
+            _ => Err(EncodeError::InvalidData("Option")),
+        }
+    }
+    fn encoded_size_with(&self, endian: Self::EndianContext, args: OptionArgs<bool, Args>) -> Result<u64, EncodeError> {
+        self.encoded_size_at(0, endian, args)
+    }
+    fn encoded_size_at(&self, position: u64, endian: Self::EndianContext, args: OptionArgs<bool, Args>) -> Result<u64, EncodeError> {
+        match (args.presence, self) {
+            (true, Some(value)) => value.encoded_size_at(position, endian, args.element_args),
+            (false, None) => Ok(0),
+            _ => Err(EncodeError::InvalidData("Option")),
+        }
+    }
+}
+
+macro_rules! impl_tuple {
+    ($i0:tt $t0:ident $a0:ident $(, $i:tt $t:ident $a:ident)*) => {
+        impl<Dir: Direction, $t0: Context<Dir>, $($t: Context<Dir, EndianContext = $t0::EndianContext>),*> Context<Dir> for ($t0, $($t,)*) {
+            type EndianContext = $t0::EndianContext;
+            type ArgsBuilder = TupleArgsBuilder;
+            fn args_builder() -> TupleArgsBuilder { TupleArgsBuilder::default() }
+        }
+
+        impl<$t0: Decode, $($t: Decode + Context<dir::Read, EndianContext = $t0::EndianContext>),*> Decode for ($t0, $($t,)*) {
+            fn decode_with<R: Read + ?Sized>(reader: &mut R, endian: Self::EndianContext, _: ()) -> Result<Self, DecodeError> {
+                Self::decode_with(reader, endian, TupleArgs { element_args: ((), $(impl_tuple!(@unit $i),)*) })
+            }
+            fn decode_tracked<R: Read + ?Sized>(reader: &mut PositionTracker<R>, endian: Self::EndianContext, _: ()) -> Result<Self, DecodeError> {
+                Self::decode_tracked(reader, endian, TupleArgs { element_args: ((), $(impl_tuple!(@unit $i),)*) })
+            }
+        }
+
+        impl<$t0: Decode<$a0>, $a0, $($t: Decode<$a> + Context<dir::Read, EndianContext = $t0::EndianContext>, $a),*> Decode<TupleArgs<($a0, $($a,)*)>> for ($t0, $($t,)*) {
+            fn decode_with<R: Read + ?Sized>(reader: &mut R, endian: Self::EndianContext, args: TupleArgs<($a0, $($a,)*)>) -> Result<Self, DecodeError> {
+                Self::decode_tracked(&mut PositionTracker::new(reader), endian, args)
+            }
+            fn decode_tracked<R: Read + ?Sized>(reader: &mut PositionTracker<R>, endian: Self::EndianContext, args: TupleArgs<($a0, $($a,)*)>) -> Result<Self, DecodeError> {
+                Ok((
+                    $t0::decode_tracked(reader, endian, args.element_args.$i0).map_err(|err| err.in_field("tuple", stringify!($i0)))?,
+                    $($t::decode_tracked(reader, endian, args.element_args.$i).map_err(|err| err.in_field("tuple", stringify!($i)))?,)*
+                ))
+            }
+        }
+
+        impl<'de, $t0: DecodeBorrowed<'de>, $($t: DecodeBorrowed<'de> + Context<dir::Read, EndianContext = $t0::EndianContext>),*> DecodeBorrowed<'de> for ($t0, $($t,)*) {
+            fn decode_borrowed_with(reader: &mut PositionTracker<&'de [u8]>, endian: Self::EndianContext, _: ()) -> Result<Self, DecodeError> {
+                Self::decode_borrowed_with(reader, endian, TupleArgs { element_args: ((), $(impl_tuple!(@unit $i),)*) })
+            }
+        }
+
+        impl<'de, $t0: DecodeBorrowed<'de, $a0>, $a0, $($t: DecodeBorrowed<'de, $a> + Context<dir::Read, EndianContext = $t0::EndianContext>, $a),*> DecodeBorrowed<'de, TupleArgs<($a0, $($a,)*)>> for ($t0, $($t,)*) {
+            fn decode_borrowed_with(reader: &mut PositionTracker<&'de [u8]>, endian: Self::EndianContext, args: TupleArgs<($a0, $($a,)*)>) -> Result<Self, DecodeError> {
+                Ok((
+                    $t0::decode_borrowed_with(reader, endian, args.element_args.$i0).map_err(|err| err.in_field("tuple", stringify!($i0)))?,
+                    $($t::decode_borrowed_with(reader, endian, args.element_args.$i).map_err(|err| err.in_field("tuple", stringify!($i)))?,)*
+                ))
+            }
+        }
+
+        impl<$t0: Encode, $($t: Encode + Context<dir::Write, EndianContext = $t0::EndianContext>),*> Encode for ($t0, $($t,)*) {
+            fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, endian: Self::EndianContext, _: ()) -> Result<(), EncodeError> {
+                self.encode_with(writer, endian, TupleArgs { element_args: ((), $(impl_tuple!(@unit $i),)*) })
+            }
+            fn encode_tracked<W: Write + ?Sized>(&self, writer: &mut PositionTracker<W>, endian: Self::EndianContext, _: ()) -> Result<(), EncodeError> {
+                self.encode_tracked(writer, endian, TupleArgs { element_args: ((), $(impl_tuple!(@unit $i),)*) })
+            }
+            fn encoded_size_with(&self, endian: Self::EndianContext, _: ()) -> Result<u64, EncodeError> {
+                self.encoded_size_at(0, endian, ())
+            }
+            fn encoded_size_at(&self, position: u64, endian: Self::EndianContext, _: ()) -> Result<u64, EncodeError> {
+                self.encoded_size_at(position, endian, TupleArgs { element_args: ((), $(impl_tuple!(@unit $i),)*) })
+            }
+        }
+
+        impl<$t0: Encode<$a0>, $a0, $($t: Encode<$a> + Context<dir::Write, EndianContext = $t0::EndianContext>, $a),*> Encode<TupleArgs<($a0, $($a,)*)>> for ($t0, $($t,)*) {
+            fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, endian: Self::EndianContext, args: TupleArgs<($a0, $($a,)*)>) -> Result<(), EncodeError> {
+                self.encode_tracked(&mut PositionTracker::new(writer), endian, args)
+            }
+            fn encode_tracked<W: Write + ?Sized>(&self, writer: &mut PositionTracker<W>, endian: Self::EndianContext, args: TupleArgs<($a0, $($a,)*)>) -> Result<(), EncodeError> {
+                self.$i0.encode_tracked(writer, endian, args.element_args.$i0).map_err(|err| err.in_field("tuple", stringify!($i0)))?;
+                $(self.$i.encode_tracked(writer, endian, args.element_args.$i).map_err(|err| err.in_field("tuple", stringify!($i)))?;)*
+                Ok(())
+            }
+            fn encoded_size_with(&self, endian: Self::EndianContext, args: TupleArgs<($a0, $($a,)*)>) -> Result<u64, EncodeError> {
+                self.encoded_size_at(0, endian, args)
+            }
+            fn encoded_size_at(&self, position: u64, endian: Self::EndianContext, args: TupleArgs<($a0, $($a,)*)>) -> Result<u64, EncodeError> {
+                let size = self.$i0.encoded_size_at(position, endian, args.element_args.$i0).map_err(|err| err.in_field("tuple", stringify!($i0)))?;
+                $(let size = size + self.$i.encoded_size_at(position + size, endian, args.element_args.$i).map_err(|err| err.in_field("tuple", stringify!($i)))?;)*
+                Ok(size)
+            }
+        }
+
+        impl<$t0: FixedSize, $($t: FixedSize),*> FixedSize for ($t0, $($t,)*) {
+            const ENCODED_SIZE: usize = $t0::ENCODED_SIZE $(+ $t::ENCODED_SIZE)*;
+        }
+    };
+    (@unit $i:tt) => { () };
+}
+
+impl_tuple!(0 T0 A0);
+impl_tuple!(0 T0 A0, 1 T1 A1);
+impl_tuple!(0 T0 A0, 1 T1 A1, 2 T2 A2);
+impl_tuple!(0 T0 A0, 1 T1 A1, 2 T2 A2, 3 T3 A3);
+impl_tuple!(0 T0 A0, 1 T1 A1, 2 T2 A2, 3 T3 A3, 4 T4 A4);
+impl_tuple!(0 T0 A0, 1 T1 A1, 2 T2 A2, 3 T3 A3, 4 T4 A4, 5 T5 A5);
+impl_tuple!(0 T0 A0, 1 T1 A1, 2 T2 A2, 3 T3 A3, 4 T4 A4, 5 T5 A5, 6 T6 A6);
+impl_tuple!(0 T0 A0, 1 T1 A1, 2 T2 A2, 3 T3 A3, 4 T4 A4, 5 T5 A5, 6 T6 A6, 7 T7 A7);
+impl_tuple!(0 T0 A0, 1 T1 A1, 2 T2 A2, 3 T3 A3, 4 T4 A4, 5 T5 A5, 6 T6 A6, 7 T7 A7, 8 T8 A8);
+impl_tuple!(0 T0 A0, 1 T1 A1, 2 T2 A2, 3 T3 A3, 4 T4 A4, 5 T5 A5, 6 T6 A6, 7 T7 A7, 8 T8 A8, 9 T9 A9);
+impl_tuple!(0 T0 A0, 1 T1 A1, 2 T2 A2, 3 T3 A3, 4 T4 A4, 5 T5 A5, 6 T6 A6, 7 T7 A7, 8 T8 A8, 9 T9 A9, 10 T10 A10);
+impl_tuple!(0 T0 A0, 1 T1 A1, 2 T2 A2, 3 T3 A3, 4 T4 A4, 5 T5 A5, 6 T6 A6, 7 T7 A7, 8 T8 A8, 9 T9 A9, 10 T10 A10, 11 T11 A11);
+
 /// Wrapper for little-endian data.
 ///
 /// Use integers or floating point numbers as [`magic`](crate::stream::Stream::magic)s:
@@ -305,12 +981,34 @@ impl<Args, T: Decode<Args>> Decode<Args> for Le<T> {
     fn decode_with<R: Read + ?Sized>(reader: &mut R, _: NoEndian, args: Args) -> Result<Self, DecodeError> {
         T::decode_with(reader, Endian::Little.into_context(), args).map(Le)
     }
+    fn decode_tracked<R: Read + ?Sized>(reader: &mut PositionTracker<R>, _: NoEndian, args: Args) -> Result<Self, DecodeError> {
+        T::decode_tracked(reader, Endian::Little.into_context(), args).map(Le)
+    }
+}
+
+impl<'de, Args, T: DecodeBorrowed<'de, Args>> DecodeBorrowed<'de, Args> for Le<T> {
+    fn decode_borrowed_with(reader: &mut PositionTracker<&'de [u8]>, _: NoEndian, args: Args) -> Result<Self, DecodeError> {
+        T::decode_borrowed_with(reader, Endian::Little.into_context(), args).map(Le)
+    }
 }
 
 impl<Args, T: Encode<Args>> Encode<Args> for Le<T> {
     fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, _: NoEndian, args: Args) -> Result<(), EncodeError> {
         self.0.encode_with(writer, Endian::Little.into_context(), args)
     }
+    fn encode_tracked<W: Write + ?Sized>(&self, writer: &mut PositionTracker<W>, _: NoEndian, args: Args) -> Result<(), EncodeError> {
+        self.0.encode_tracked(writer, Endian::Little.into_context(), args)
+    }
+    fn encoded_size_with(&self, _: NoEndian, args: Args) -> Result<u64, EncodeError> {
+        self.0.encoded_size_with(Endian::Little.into_context(), args)
+    }
+    fn encoded_size_at(&self, position: u64, _: NoEndian, args: Args) -> Result<u64, EncodeError> {
+        self.0.encoded_size_at(position, Endian::Little.into_context(), args)
+    }
+}
+
+impl<T: FixedSize> FixedSize for Le<T> {
+    const ENCODED_SIZE: usize = T::ENCODED_SIZE;
 }
 
 /// Wrapper for big-endian data.
@@ -344,14 +1042,237 @@ impl<Args, T: Decode<Args>> Decode<Args> for Be<T> {
     fn decode_with<R: Read + ?Sized>(reader: &mut R, _: NoEndian, args: Args) -> Result<Self, DecodeError> {
         T::decode_with(reader, Endian::Big.into_context(), args).map(Be)
     }
+    fn decode_tracked<R: Read + ?Sized>(reader: &mut PositionTracker<R>, _: NoEndian, args: Args) -> Result<Self, DecodeError> {
+        T::decode_tracked(reader, Endian::Big.into_context(), args).map(Be)
+    }
+}
+
+impl<'de, Args, T: DecodeBorrowed<'de, Args>> DecodeBorrowed<'de, Args> for Be<T> {
+    fn decode_borrowed_with(reader: &mut PositionTracker<&'de [u8]>, _: NoEndian, args: Args) -> Result<Self, DecodeError> {
+        T::decode_borrowed_with(reader, Endian::Big.into_context(), args).map(Be)
+    }
 }
 
 impl<Args, T: Encode<Args>> Encode<Args> for Be<T> {
     fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, _: NoEndian, args: Args) -> Result<(), EncodeError> {
         self.0.encode_with(writer, Endian::Big.into_context(), args)
     }
+    fn encode_tracked<W: Write + ?Sized>(&self, writer: &mut PositionTracker<W>, _: NoEndian, args: Args) -> Result<(), EncodeError> {
+        self.0.encode_tracked(writer, Endian::Big.into_context(), args)
+    }
+    fn encoded_size_with(&self, _: NoEndian, args: Args) -> Result<u64, EncodeError> {
+        self.0.encoded_size_with(Endian::Big.into_context(), args)
+    }
+    fn encoded_size_at(&self, position: u64, _: NoEndian, args: Args) -> Result<u64, EncodeError> {
+        self.0.encoded_size_at(position, Endian::Big.into_context(), args)
+    }
+}
+
+impl<T: FixedSize> FixedSize for Be<T> {
+    const ENCODED_SIZE: usize = T::ENCODED_SIZE;
+}
+
+/// Unsigned LEB128 variable-length integer, as in DWARF and WebAssembly.
+///
+/// Every byte holds 7 bits of the value, from the least significant bits, with the highest bit
+/// set for all bytes but the last one. Decoding fails if the value does not fit in `T`:
+/// ```
+/// # use bin_data::data::{Decode, Encode, Uleb128};
+/// let mut buffer = Vec::new();
+/// Uleb128(624485_u32).encode(&mut buffer).unwrap();
+/// assert_eq!(buffer, [0xE5, 0x8E, 0x26]);
+/// assert_eq!(Uleb128::<u32>::decode(&mut buffer.as_slice()).unwrap(), Uleb128(624485));
+/// assert!(Uleb128::<u16>::decode(&mut buffer.as_slice()).is_err());
+/// ```
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub struct Uleb128<T>(pub T);
+
+/// Signed LEB128 variable-length integer, in two's complement.
+///
+/// The same as [`Uleb128`], except that the highest bit in the last group is the sign bit:
+/// ```
+/// # use bin_data::data::{Encode, Sleb128};
+/// let mut buffer = Vec::new();
+/// Sleb128(-123456_i32).encode(&mut buffer).unwrap();
+/// assert_eq!(buffer, [0xC0, 0xBB, 0x78]);
+/// ```
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub struct Sleb128<T>(pub T);
+
+/// Signed variable-length integer with zigzag encoding, as in Protocol Buffers.
+///
+/// Values of small magnitude are mapped to small unsigned integers (`0, -1, 1, -2, ...` to
+/// `0, 1, 2, 3, ...`), and then encoded as [`Uleb128`]:
+/// ```
+/// # use bin_data::data::{Encode, ZigZag};
+/// let mut buffer = Vec::new();
+/// ZigZag(-2_i32).encode(&mut buffer).unwrap();
+/// ZigZag(300_i32).encode(&mut buffer).unwrap();
+/// assert_eq!(buffer, [0x03, 0xD8, 0x04]);
+/// ```
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub struct ZigZag<T>(pub T);
+
+/// Unsigned variable-length quantity, as in Standard MIDI Files.
+///
+/// Every byte holds 7 bits of the value, from the most significant bits, with the highest bit set
+/// for all bytes but the last one:
+/// ```
+/// # use bin_data::data::{Encode, Vlq};
+/// let mut buffer = Vec::new();
+/// Vlq(0x3FFF_u32).encode(&mut buffer).unwrap();
+/// assert_eq!(buffer, [0xFF, 0x7F]);
+/// ```
+#[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
+pub struct Vlq<T>(pub T);
+
+/// Maximum number of bytes in a variable-length integer, enough for 64-bit integers.
+const VARINT_MAX_BYTES: usize = 10;
+
+/// Read the 7-bit groups of a variable-length integer, in their order in the input.
+fn read_varint_groups<R: Read + ?Sized>(
+    reader: &mut R, type_name: &'static str,
+) -> Result<impl Iterator<Item = u8>, DecodeError> {
+    let mut groups = [0_u8; VARINT_MAX_BYTES];
+    for count in 0..VARINT_MAX_BYTES {
+        let mut byte = [0_u8];
+        reader.read_exact(&mut byte).map_err(|err| DecodeError::IncompleteData(type_name, err))?;
+        groups[count] = byte[0] & 0x7F;
+        if byte[0] & 0x80 == 0 {
+            return Ok(groups.into_iter().take(count + 1));
+        }
+    }
+    Err(DecodeError::InvalidData(type_name))
+}
+
+fn decode_uleb128<R: Read + ?Sized>(reader: &mut R, type_name: &'static str) -> Result<u128, DecodeError> {
+    let groups = read_varint_groups(reader, type_name)?;
+    Ok(groups.enumerate().fold(0, |value, (k, group)| value | (group as u128) << (7 * k)))
+}
+
+fn decode_sleb128<R: Read + ?Sized>(reader: &mut R, type_name: &'static str) -> Result<i128, DecodeError> {
+    let groups = read_varint_groups(reader, type_name)?;
+    let (value, bits) = groups.fold((0, 0), |(value, bits), group| (value | (group as i128) << bits, bits + 7));
+    // sign extension from the highest bit in the last group
+    Ok(value << (128 - bits) >> (128 - bits))
+}
+
+fn decode_zigzag<R: Read + ?Sized>(reader: &mut R, type_name: &'static str) -> Result<i128, DecodeError> {
+    let value = decode_uleb128(reader, type_name)?;
+    Ok((value >> 1) as i128 ^ -((value & 1) as i128))
+}
+
+fn decode_vlq<R: Read + ?Sized>(reader: &mut R, type_name: &'static str) -> Result<u128, DecodeError> {
+    let groups = read_varint_groups(reader, type_name)?;
+    Ok(groups.fold(0, |value, group| value << 7 | group as u128))
+}
+
+/// Encoded bytes of a variable-length integer, and how many of them are in use.
+type VarintBytes = ([u8; VARINT_MAX_BYTES], usize);
+
+fn encode_uleb128(mut value: u128) -> VarintBytes {
+    let mut bytes = [0_u8; VARINT_MAX_BYTES];
+    let mut count = 0;
+    loop {
+        let group = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            bytes[count] = group;
+            return (bytes, count + 1);
+        }
+        bytes[count] = group | 0x80;
+        count += 1;
+    }
+}
+
+fn encode_sleb128(mut value: i128) -> VarintBytes {
+    let mut bytes = [0_u8; VARINT_MAX_BYTES];
+    let mut count = 0;
+    loop {
+        let group = (value & 0x7F) as u8;
+        value >>= 7;
+        // stop when the remaining bits are all copies of the sign bit
+        if (value == 0 && group & 0x40 == 0) || (value == -1 && group & 0x40 != 0) {
+            bytes[count] = group;
+            return (bytes, count + 1);
+        }
+        bytes[count] = group | 0x80;
+        count += 1;
+    }
+}
+
+fn zigzag(value: i128) -> u128 {
+    ((value << 1) ^ (value >> 127)) as u128
+}
+
+fn encode_zigzag(value: i128) -> VarintBytes {
+    encode_uleb128(zigzag(value))
+}
+
+fn encode_vlq(value: u128) -> VarintBytes {
+    let (mut bytes, count) = encode_uleb128(value);
+    bytes[..count].reverse();
+    bytes[0] |= 0x80;
+    bytes[count - 1] &= 0x7F;
+    (bytes, count)
+}
+
+fn uleb128_size(value: u128) -> u64 {
+    let bits = u128::BITS - value.leading_zeros();
+    (bits.max(1) as u64).div_ceil(7)
+}
+
+fn sleb128_size(value: i128) -> u64 {
+    // significant bits, plus one for the sign
+    let magnitude = if value < 0 { !value } else { value };
+    let bits = i128::BITS - magnitude.leading_zeros() + 1;
+    (bits as u64).div_ceil(7)
+}
+
+fn zigzag_size(value: i128) -> u64 {
+    uleb128_size(zigzag(value))
+}
+
+fn vlq_size(value: u128) -> u64 {
+    uleb128_size(value)
+}
+
+macro_rules! impl_varint {
+    ($wrapper:ident($repr:ty): $decode:ident, $encode:ident, $size:ident; $($t:ty),+ $(,)?) => {
+        impl<Dir: Direction, T> Context<Dir> for $wrapper<T> {
+            type EndianContext = NoEndian;
+            type ArgsBuilder = NoArgs;
+            fn args_builder() -> NoArgs { NoArgs }
+        }
+
+        $(
+            impl Decode for $wrapper<$t> {
+                fn decode_with<R: Read + ?Sized>(reader: &mut R, _: NoEndian, _: ()) -> Result<Self, DecodeError> {
+                    let value = $decode(reader, stringify!($wrapper))?;
+                    <$t>::try_from(value).map($wrapper).map_err(|_| DecodeError::InvalidData(stringify!($wrapper)))
+                }
+            }
+
+            decode_borrowed_by_decode!($wrapper<$t>: ());
+
+            impl Encode for $wrapper<$t> {
+                fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, _: NoEndian, _: ()) -> Result<(), EncodeError> {
+                    let (bytes, count) = $encode(self.0 as $repr);
+                    writer.write_all(&bytes[..count]).map_err(EncodeError::from)
+                }
+                fn encoded_size_with(&self, _: NoEndian, _: ()) -> Result<u64, EncodeError> {
+                    Ok($size(self.0 as $repr))
+                }
+            }
+        )+
+    }
 }
 
+impl_varint!(Uleb128(u128): decode_uleb128, encode_uleb128, uleb128_size; u8, u16, u32, u64, usize);
+impl_varint!(Sleb128(i128): decode_sleb128, encode_sleb128, sleb128_size; i8, i16, i32, i64, isize);
+impl_varint!(ZigZag(i128): decode_zigzag, encode_zigzag, zigzag_size; i8, i16, i32, i64, isize);
+impl_varint!(Vlq(u128): decode_vlq, encode_vlq, vlq_size; u8, u16, u32, u64, usize);
+
 impl<T: Context<dir::Read>> Context<dir::Read> for Vec<T> {
     type EndianContext = T::EndianContext;
     type ArgsBuilder = VecArgsBuilder<Required>;
@@ -361,8 +1282,69 @@ impl<T: Context<dir::Read>> Context<dir::Read> for Vec<T> {
 impl<Args, T> Decode<VecArgs<Args>> for Vec<T>
     where Args: Iterator, T: Decode<Args::Item> {
     fn decode_with<S: Read + ?Sized>(s: &mut S, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<Self, DecodeError> {
-        args.element_args.map(|arg| T::decode_with(s, endian, arg)).collect()
+        Self::decode_tracked(&mut PositionTracker::new(s), endian, args)
+    }
+    fn decode_tracked<S: Read + ?Sized>(s: &mut PositionTracker<S>, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<Self, DecodeError> {
+        decode_count(s, args.element_args, |s, arg| T::decode_tracked(s, endian, arg))
+    }
+}
+
+impl<'de, Args, T> DecodeBorrowed<'de, VecArgs<Args>> for Vec<T>
+    where Args: Iterator, T: DecodeBorrowed<'de, Args::Item> {
+    fn decode_borrowed_with(s: &mut PositionTracker<&'de [u8]>, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<Self, DecodeError> {
+        decode_count(s, args.element_args, |s, arg| T::decode_borrowed_with(s, endian, arg))
+    }
+}
+
+fn decode_count<S: ?Sized, A, T>(
+    s: &mut S, args: impl Iterator<Item = A>,
+    mut decode_element: impl FnMut(&mut S, A) -> Result<T, DecodeError>,
+) -> Result<Vec<T>, DecodeError> {
+    args.enumerate()
+        .map(|(index, arg)| decode_element(s, arg).map_err(|err| err.at_index(index)))
+        .collect()
+}
+
+/// Decoding fails with [`DecodeError::IncompleteData`] if the stream ends in the middle of an
+/// element, and with [`DecodeError::InvalidData`] if an element consumes no input at all.
+/// ```
+/// # use bin_data::context::{Context, Endian, ArgsBuilderFinished};
+/// # use bin_data::data::Decode;
+/// # use bin_data::stream::dir;
+/// let args = <Vec<u16> as Context<dir::Read>>::args_builder().until_eof().finish();
+/// assert_eq!(Vec::<u16>::decode_with(&mut [1, 0, 2, 0].as_ref(), Endian::Little, args).unwrap(), [1, 2]);
+/// ```
+impl<Args, T> Decode<VecArgs<UntilEof<Args>>> for Vec<T>
+    where Args: Iterator, T: Decode<Args::Item> {
+    fn decode_with<S: Read + ?Sized>(s: &mut S, endian: Self::EndianContext, args: VecArgs<UntilEof<Args>>) -> Result<Self, DecodeError> {
+        Self::decode_tracked(&mut PositionTracker::new(s), endian, args)
+    }
+    fn decode_tracked<S: Read + ?Sized>(s: &mut PositionTracker<S>, endian: Self::EndianContext, args: VecArgs<UntilEof<Args>>) -> Result<Self, DecodeError> {
+        decode_until_eof(s, args.element_args.0, PositionTracker::at_end, |s, arg| T::decode_tracked(s, endian, arg))
+    }
+}
+
+impl<'de, Args, T> DecodeBorrowed<'de, VecArgs<UntilEof<Args>>> for Vec<T>
+    where Args: Iterator, T: DecodeBorrowed<'de, Args::Item> {
+    fn decode_borrowed_with(s: &mut PositionTracker<&'de [u8]>, endian: Self::EndianContext, args: VecArgs<UntilEof<Args>>) -> Result<Self, DecodeError> {
+        decode_until_eof(s, args.element_args.0, |s| Ok(s.is_exhausted()), |s, arg| T::decode_borrowed_with(s, endian, arg))
+    }
+}
+
+fn decode_until_eof<S: Read + ?Sized, A, T>(
+    s: &mut PositionTracker<S>, args: impl Iterator<Item = A>,
+    mut at_end: impl FnMut(&mut PositionTracker<S>) -> std::io::Result<bool>,
+    mut decode_element: impl FnMut(&mut PositionTracker<S>, A) -> Result<T, DecodeError>,
+) -> Result<Vec<T>, DecodeError> {
+    let mut elements = Vec::new();
+    for (index, arg) in args.enumerate() {
+        if at_end(s).map_err(|err| DecodeError::IncompleteData("Vec", err))? { break; }
+        let position = s.position();
+        elements.push(decode_element(s, arg).map_err(|err| err.at_index(index))?);
+        // an element consuming nothing would never reach the end
+        if s.position() == position { return Err(DecodeError::InvalidData("Vec")); }
     }
+    Ok(elements)
 }
 
 impl<T: Context<dir::Write>> Context<dir::Write> for Vec<T> {
@@ -376,6 +1358,15 @@ impl<Args, T> Encode<VecArgs<Args>> for Vec<T>
     fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<(), EncodeError> {
         self.deref().encode_with(writer, endian, args)
     }
+    fn encode_tracked<W: Write + ?Sized>(&self, writer: &mut PositionTracker<W>, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<(), EncodeError> {
+        self.deref().encode_tracked(writer, endian, args)
+    }
+    fn encoded_size_with(&self, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<u64, EncodeError> {
+        self.deref().encoded_size_with(endian, args)
+    }
+    fn encoded_size_at(&self, position: u64, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<u64, EncodeError> {
+        self.deref().encoded_size_at(position, endian, args)
+    }
 }
 
 impl<T: Context<dir::Write>> Context<dir::Write> for [T] {
@@ -387,8 +1378,17 @@ impl<T: Context<dir::Write>> Context<dir::Write> for [T] {
 impl<Args, T> Encode<VecArgs<Args>> for [T]
     where Args: Iterator, T: Encode<Args::Item> {
     fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<(), EncodeError> {
+        self.encode_tracked(&mut PositionTracker::new(writer), endian, args)
+    }
+    fn encode_tracked<W: Write + ?Sized>(&self, writer: &mut PositionTracker<W>, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<(), EncodeError> {
         encode_iter(writer, "Vec", endian, self, args.element_args)
     }
+    fn encoded_size_with(&self, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<u64, EncodeError> {
+        self.encoded_size_at(0, endian, args)
+    }
+    fn encoded_size_at(&self, position: u64, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<u64, EncodeError> {
+        encoded_size_iter("Vec", position, endian, self, args.element_args)
+    }
 }
 
 impl<T: Context<dir::Read>> Context<dir::Read> for Box<[T]> {
@@ -402,6 +1402,106 @@ impl<Args, T> Decode<VecArgs<Args>> for Box<[T]>
     fn decode_with<S: Read + ?Sized>(s: &mut S, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<Self, DecodeError> {
         Vec::<T>::decode_with(s, endian, args).map(Vec::into_boxed_slice)
     }
+    fn decode_tracked<S: Read + ?Sized>(s: &mut PositionTracker<S>, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<Self, DecodeError> {
+        Vec::<T>::decode_tracked(s, endian, args).map(Vec::into_boxed_slice)
+    }
+}
+
+impl<'de, Args, T> DecodeBorrowed<'de, VecArgs<Args>> for Box<[T]>
+    where Args: Iterator, T: DecodeBorrowed<'de, Args::Item> {
+    fn decode_borrowed_with(s: &mut PositionTracker<&'de [u8]>, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<Self, DecodeError> {
+        Vec::<T>::decode_borrowed_with(s, endian, args).map(Vec::into_boxed_slice)
+    }
+}
+
+impl<Args, T> Decode<VecArgs<UntilEof<Args>>> for Box<[T]>
+    where Args: Iterator, T: Decode<Args::Item> {
+    fn decode_with<S: Read + ?Sized>(s: &mut S, endian: Self::EndianContext, args: VecArgs<UntilEof<Args>>) -> Result<Self, DecodeError> {
+        Vec::<T>::decode_with(s, endian, args).map(Vec::into_boxed_slice)
+    }
+    fn decode_tracked<S: Read + ?Sized>(s: &mut PositionTracker<S>, endian: Self::EndianContext, args: VecArgs<UntilEof<Args>>) -> Result<Self, DecodeError> {
+        Vec::<T>::decode_tracked(s, endian, args).map(Vec::into_boxed_slice)
+    }
+}
+
+impl<'de, Args, T> DecodeBorrowed<'de, VecArgs<UntilEof<Args>>> for Box<[T]>
+    where Args: Iterator, T: DecodeBorrowed<'de, Args::Item> {
+    fn decode_borrowed_with(s: &mut PositionTracker<&'de [u8]>, endian: Self::EndianContext, args: VecArgs<UntilEof<Args>>) -> Result<Self, DecodeError> {
+        Vec::<T>::decode_borrowed_with(s, endian, args).map(Vec::into_boxed_slice)
+    }
+}
+
+/// Fixed-size arrays take per-element arguments as in [`VecArgsBuilder`], but without a count.
+/// ```
+/// # use bin_data::data::{Decode, Encode, Le};
+/// let table = Le::<[u16; 3]>::decode(&mut [1, 0, 2, 0, 3, 0].as_ref()).unwrap();
+/// assert_eq!(table, Le([1, 2, 3]));
+/// let mut buffer = Vec::new();
+/// table.encode(&mut buffer).unwrap();
+/// assert_eq!(buffer, [1, 0, 2, 0, 3, 0]);
+/// ```
+impl<Dir: Direction, T: Context<Dir>, const N: usize> Context<Dir> for [T; N] {
+    type EndianContext = T::EndianContext;
+    type ArgsBuilder = VecArgsBuilder<Provided<std::iter::Repeat<()>>>;
+    fn args_builder() -> Self::ArgsBuilder { Self::ArgsBuilder::new() }
+}
+
+/// Decoding fails with [`DecodeError::InvalidData`] if there are less than `N` arguments.
+impl<Args, T, const N: usize> Decode<VecArgs<Args>> for [T; N]
+    where Args: Iterator, T: Decode<Args::Item> {
+    fn decode_with<S: Read + ?Sized>(s: &mut S, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<Self, DecodeError> {
+        Self::decode_tracked(&mut PositionTracker::new(s), endian, args)
+    }
+    fn decode_tracked<S: Read + ?Sized>(s: &mut PositionTracker<S>, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<Self, DecodeError> {
+        decode_array(s, args.element_args, |s, arg| T::decode_tracked(s, endian, arg))
+    }
+}
+
+impl<'de, Args, T, const N: usize> DecodeBorrowed<'de, VecArgs<Args>> for [T; N]
+    where Args: Iterator, T: DecodeBorrowed<'de, Args::Item> {
+    fn decode_borrowed_with(s: &mut PositionTracker<&'de [u8]>, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<Self, DecodeError> {
+        decode_array(s, args.element_args, |s, arg| T::decode_borrowed_with(s, endian, arg))
+    }
+}
+
+fn decode_array<S: ?Sized, A, T, const N: usize>(
+    s: &mut S, mut args: impl Iterator<Item = A>,
+    mut decode_element: impl FnMut(&mut S, A) -> Result<T, DecodeError>,
+) -> Result<[T; N], DecodeError> {
+    let mut error = None;
+    // stop decoding after the first error, and unwrap the elements only if all succeeded
+    let elements: [Option<T>; N] = std::array::from_fn(|index| {
+        if error.is_some() { return None; }
+        let result = match args.next() {
+            Some(arg) => decode_element(s, arg).map_err(|err| err.at_index(index)),
+            None => Err(DecodeError::InvalidData("array")),
+        };
+        result.map_err(|err| error = Some(err)).ok()
+    });
+    match error {
+        Some(err) => Err(err),
+        None => Ok(elements.map(Option::unwrap)),
+    }
+}
+
+impl<Args, T, const N: usize> Encode<VecArgs<Args>> for [T; N]
+    where Args: Iterator, T: Encode<Args::Item> {
+    fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<(), EncodeError> {
+        self.encode_tracked(&mut PositionTracker::new(writer), endian, args)
+    }
+    fn encode_tracked<W: Write + ?Sized>(&self, writer: &mut PositionTracker<W>, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<(), EncodeError> {
+        encode_iter(writer, "array", endian, self, args.element_args)
+    }
+    fn encoded_size_with(&self, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<u64, EncodeError> {
+        self.encoded_size_at(0, endian, args)
+    }
+    fn encoded_size_at(&self, position: u64, endian: Self::EndianContext, args: VecArgs<Args>) -> Result<u64, EncodeError> {
+        encoded_size_iter("array", position, endian, self, args.element_args)
+    }
+}
+
+impl<T: FixedSize, const N: usize> FixedSize for [T; N] {
+    const ENCODED_SIZE: usize = T::ENCODED_SIZE * N;
 }
 
 impl Context<dir::Read> for String {
@@ -415,19 +1515,37 @@ impl Decode<StrArgs> for String {
         use DecodeError::IncompleteData;
         let mut buffer = vec![0_u8; args.count];
         reader.read_exact(&mut buffer).map_err(|err| IncompleteData("String", err))?;
-        String::from_utf8(buffer).map_err(DecodeError::from)
+        match args.encoding {
+            Encoding::Utf8 => String::from_utf8(buffer).map_err(DecodeError::from),
+            encoding => decode_single_byte(&buffer, encoding, "String"),
+        }
     }
 }
 
+decode_borrowed_by_decode!(String: StrArgs);
+
 impl Context<dir::Write> for String {
     type EndianContext = NoEndian;
-    type ArgsBuilder = NoArgs;
-    fn args_builder() -> NoArgs { NoArgs }
+    type ArgsBuilder = StrArgsBuilder<()>;
+    fn args_builder() -> StrArgsBuilder<()> { StrArgsBuilder::default() }
 }
 
+impl Encode<Encoding> for String {
+    fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, endian: NoEndian, encoding: Encoding) -> Result<(), EncodeError> {
+        self.as_str().encode_with(writer, endian, encoding)
+    }
+    fn encoded_size_with(&self, endian: NoEndian, encoding: Encoding) -> Result<u64, EncodeError> {
+        self.as_str().encoded_size_with(endian, encoding)
+    }
+}
+
+/// Encode as UTF-8, e.g., as elements of a [`Vec`] with default arguments.
 impl Encode for String {
-    fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, _: NoEndian, _: ()) -> Result<(), EncodeError> {
-        writer.write_all(self.as_bytes()).map_err(EncodeError::from)
+    fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, endian: NoEndian, _: ()) -> Result<(), EncodeError> {
+        self.encode_with(writer, endian, Encoding::Utf8)
+    }
+    fn encoded_size_with(&self, _: NoEndian, _: ()) -> Result<u64, EncodeError> {
+        Ok(self.len() as u64)
     }
 }
 
@@ -443,16 +1561,470 @@ impl Decode<StrArgs> for Box<str> {
     }
 }
 
+decode_borrowed_by_decode!(Box<str>: StrArgs);
+
 impl View<String> for str {}
 
 impl Context<dir::Write> for str {
     type EndianContext = NoEndian;
+    type ArgsBuilder = StrArgsBuilder<()>;
+    fn args_builder() -> StrArgsBuilder<()> { StrArgsBuilder::default() }
+}
+
+impl Encode<Encoding> for str {
+    fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, _: NoEndian, encoding: Encoding) -> Result<(), EncodeError> {
+        if encoding == Encoding::Utf8 || self.is_ascii() {
+            return writer.write_all(self.as_bytes()).map_err(EncodeError::from);
+        }
+        let mut buffer = [0; 64];
+        let mut chars = self.chars();
+        loop {
+            let mut len = 0;
+            for (byte, character) in buffer.iter_mut().zip(&mut chars) {
+                *byte = encode_char(character, encoding)?;
+                len += 1;
+            }
+            if len == 0 { return Ok(()); }
+            writer.write_all(&buffer[..len])?;
+        }
+    }
+    fn encoded_size_with(&self, _: NoEndian, encoding: Encoding) -> Result<u64, EncodeError> {
+        if encoding == Encoding::Utf8 || self.is_ascii() {
+            return Ok(self.len() as u64);
+        }
+        self.chars().map(|character| encode_char(character, encoding).map(|_| 1)).sum()
+    }
+}
+
+/// Encode as UTF-8, e.g., as elements of a [`Vec`] with default arguments.
+impl Encode for str {
+    fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, endian: NoEndian, _: ()) -> Result<(), EncodeError> {
+        self.encode_with(writer, endian, Encoding::Utf8)
+    }
+    fn encoded_size_with(&self, _: NoEndian, _: ()) -> Result<u64, EncodeError> {
+        Ok(self.len() as u64)
+    }
+}
+
+/// Characters for bytes `0x80..=0x9F` in Windows-1252, the rest coincides with Latin-1.
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '\u{20AC}', '\u{81}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{8D}', '\u{017D}', '\u{8F}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{9D}', '\u{017E}', '\u{0178}',
+];
+
+fn decode_single_byte(bytes: &[u8], encoding: Encoding, type_name: &'static str) -> Result<String, DecodeError> {
+    bytes.iter().map(|&byte| match (encoding, byte) {
+        (_, 0x00..=0x7F) => Ok(byte as char),
+        (Encoding::Windows1252, 0x80..=0x9F) => Ok(WINDOWS_1252_HIGH[byte as usize - 0x80]),
+        (Encoding::Latin1 | Encoding::Windows1252, _) => Ok(byte as char),
+        _ => Err(DecodeError::InvalidData(type_name)),
+    }).collect()
+}
+
+/// Encode a character in a single-byte encoding.
+fn encode_char(character: char, encoding: Encoding) -> Result<u8, EncodeError> {
+    match (encoding, character as u32) {
+        (_, 0x00..=0x7F) => Ok(character as u8),
+        (Encoding::Latin1, 0x80..=0xFF) => Ok(character as u8),
+        (Encoding::Windows1252, 0xA0..=0xFF) => Ok(character as u8),
+        (Encoding::Windows1252, _) => WINDOWS_1252_HIGH.iter()
+            .position(|&c| c == character)
+            .map(|k| 0x80 + k as u8)
+            .ok_or(EncodeError::UnmappableCharacter { character, encoding }),
+        _ => Err(EncodeError::UnmappableCharacter { character, encoding }),
+    }
+}
+
+/// NUL-terminated string, as in C.
+///
+/// When decoding, bytes are read until a zero byte, which is consumed but not included in the
+/// string. The length before the terminator can be limited by `max_length` in the arguments.
+/// ```
+/// # use bin_data::data::{Decode, Encode, NullTerminated};
+/// let name = NullTerminated::<String>::decode(&mut b"zombie\0rest".as_ref()).unwrap();
+/// assert_eq!(name.0, "zombie");
+/// let mut buffer = Vec::new();
+/// name.encode(&mut buffer).unwrap();
+/// assert_eq!(buffer, b"zombie\0");
+/// ```
+#[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct NullTerminated<T>(pub T);
+
+fn read_null_terminated<R: Read + ?Sized>(
+    reader: &mut R, type_name: &'static str, args: NullTerminatedArgs,
+) -> Result<Vec<u8>, DecodeError> {
+    let mut bytes = Vec::new();
+    loop {
+        let mut byte = 0;
+        reader.read_exact(std::slice::from_mut(&mut byte))
+            .map_err(|err| DecodeError::IncompleteData(type_name, err))?;
+        if byte == 0 { return Ok(bytes); }
+        if args.max_length.is_some_and(|max_length| bytes.len() >= max_length) {
+            return Err(DecodeError::InvalidData(type_name));
+        }
+        bytes.push(byte);
+    }
+}
+
+fn null_terminated_size(bytes: &[u8], type_name: &'static str, args: NullTerminatedArgs) -> Result<u64, EncodeError> {
+    if bytes.contains(&0) {
+        return Err(EncodeError::InvalidData(type_name));
+    }
+    if args.max_length.is_some_and(|max_length| bytes.len() > max_length) {
+        return Err(EncodeError::InvalidArgument(type_name, "string too long"));
+    }
+    Ok(bytes.len() as u64 + 1)
+}
+
+fn write_null_terminated<W: Write + ?Sized>(
+    writer: &mut W, bytes: &[u8], type_name: &'static str, args: NullTerminatedArgs,
+) -> Result<(), EncodeError> {
+    null_terminated_size(bytes, type_name, args)?;
+    writer.write_all(bytes)?;
+    writer.write_all(&[0]).map_err(EncodeError::from)
+}
+
+impl<Dir: Direction> Context<Dir> for NullTerminated<String> {
+    type EndianContext = NoEndian;
+    type ArgsBuilder = NullTerminatedArgsBuilder;
+    fn args_builder() -> Self::ArgsBuilder { NullTerminatedArgsBuilder::default() }
+}
+
+impl Decode<NullTerminatedArgs> for NullTerminated<String> {
+    fn decode_with<R: Read + ?Sized>(reader: &mut R, _: NoEndian, args: NullTerminatedArgs) -> Result<Self, DecodeError> {
+        let bytes = read_null_terminated(reader, "NullTerminated", args)?;
+        String::from_utf8(bytes).map(NullTerminated).map_err(DecodeError::from)
+    }
+}
+
+decode_borrowed_by_decode!(NullTerminated<String>: NullTerminatedArgs);
+
+impl Encode<NullTerminatedArgs> for NullTerminated<String> {
+    fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, _: NoEndian, args: NullTerminatedArgs) -> Result<(), EncodeError> {
+        write_null_terminated(writer, self.0.as_bytes(), "NullTerminated", args)
+    }
+    fn encoded_size_with(&self, _: NoEndian, args: NullTerminatedArgs) -> Result<u64, EncodeError> {
+        null_terminated_size(self.0.as_bytes(), "NullTerminated", args)
+    }
+}
+
+impl<Dir: Direction> Context<Dir> for CString {
+    type EndianContext = NoEndian;
+    type ArgsBuilder = NullTerminatedArgsBuilder;
+    fn args_builder() -> Self::ArgsBuilder { NullTerminatedArgsBuilder::default() }
+}
+
+impl Decode<NullTerminatedArgs> for CString {
+    fn decode_with<R: Read + ?Sized>(reader: &mut R, _: NoEndian, args: NullTerminatedArgs) -> Result<Self, DecodeError> {
+        let bytes = read_null_terminated(reader, "CString", args)?;
+        Ok(CString::new(bytes).expect("no interior NUL"))
+    }
+}
+
+decode_borrowed_by_decode!(CString: NullTerminatedArgs);
+
+impl Encode<NullTerminatedArgs> for CString {
+    fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, _: NoEndian, args: NullTerminatedArgs) -> Result<(), EncodeError> {
+        write_null_terminated(writer, self.as_bytes(), "CString", args)
+    }
+    fn encoded_size_with(&self, _: NoEndian, args: NullTerminatedArgs) -> Result<u64, EncodeError> {
+        null_terminated_size(self.as_bytes(), "CString", args)
+    }
+}
+
+/// String stored in exactly `N` bytes, padded at the end.
+///
+/// Trailing padding bytes (zeros by default, or `padding` in the arguments) are trimmed when
+/// decoding, and added when encoding. Encoding fails if the string is longer than `N` bytes, or
+/// if it ends with the padding byte, which would not survive decoding.
+/// ```
+/// # use bin_data::data::{Decode, Encode, FixedStr};
+/// let name = FixedStr::<8>::decode(&mut b"wallnut\0".as_ref()).unwrap();
+/// assert_eq!(name.0, "wallnut");
+/// let mut buffer = Vec::new();
+/// FixedStr::<8>("pea".to_string()).encode(&mut buffer).unwrap();
+/// assert_eq!(buffer, b"pea\0\0\0\0\0");
+/// ```
+#[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct FixedStr<const N: usize>(pub String);
+
+impl<Dir: Direction, const N: usize> Context<Dir> for FixedStr<N> {
+    type EndianContext = NoEndian;
+    type ArgsBuilder = FixedStrArgsBuilder;
+    fn args_builder() -> Self::ArgsBuilder { FixedStrArgsBuilder::default() }
+}
+
+impl<const N: usize> Decode<FixedStrArgs> for FixedStr<N> {
+    fn decode_with<R: Read + ?Sized>(reader: &mut R, _: NoEndian, args: FixedStrArgs) -> Result<Self, DecodeError> {
+        let mut buffer = vec![0_u8; N];
+        reader.read_exact(&mut buffer).map_err(|err| DecodeError::IncompleteData("FixedStr", err))?;
+        let length = buffer.iter().rposition(|&byte| byte != args.padding).map_or(0, |last| last + 1);
+        buffer.truncate(length);
+        String::from_utf8(buffer).map(FixedStr).map_err(DecodeError::from)
+    }
+}
+
+decode_borrowed_by_decode!(FixedStr<N>: FixedStrArgs; const N: usize);
+
+impl<const N: usize> Encode<FixedStrArgs> for FixedStr<N> {
+    fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, _: NoEndian, args: FixedStrArgs) -> Result<(), EncodeError> {
+        let padding = self.padding_len(args)?;
+        writer.write_all(self.0.as_bytes())?;
+        writer.write_all(&vec![args.padding; padding]).map_err(EncodeError::from)
+    }
+    fn encoded_size_with(&self, _: NoEndian, args: FixedStrArgs) -> Result<u64, EncodeError> {
+        self.padding_len(args).map(|_| N as u64)
+    }
+}
+
+impl<const N: usize> FixedStr<N> {
+    fn padding_len(&self, args: FixedStrArgs) -> Result<usize, EncodeError> {
+        // trailing padding bytes in the string itself would be trimmed when decoding
+        if self.0.as_bytes().last() == Some(&args.padding) {
+            return Err(EncodeError::InvalidArgument("FixedStr", "string ends with padding"));
+        }
+        N.checked_sub(self.0.len()).ok_or(EncodeError::InvalidArgument("FixedStr", "string too long"))
+    }
+}
+
+impl<const N: usize> FixedSize for FixedStr<N> {
+    const ENCODED_SIZE: usize = N;
+}
+
+/// String encoded in UTF-16, with the endianness of the enclosing type.
+///
+/// The length is given as a number of code units when decoding, by `count` in the arguments. Use
+/// [`NullTerminated<Utf16String>`] for strings terminated by a zero code unit instead, whose
+/// `max_length` is also measured in code units.
+/// ```
+/// # use bin_data::context::{ArgsBuilderFinished, Context, Endian};
+/// # use bin_data::data::{Decode, Encode, NullTerminated, Utf16String};
+/// # use bin_data::stream::dir;
+/// let input = [b'h', 0, b'i', 0, 0, 0];
+/// let args = <Utf16String as Context<dir::Read>>::args_builder().count(2).finish();
+/// let text = Utf16String::decode_with(&mut input.as_ref(), Endian::Little, args).unwrap();
+/// assert_eq!(text.0, "hi");
+/// let args = <NullTerminated<Utf16String> as Context<dir::Read>>::args_builder().finish();
+/// let text = NullTerminated::<Utf16String>::decode_with(&mut input.as_ref(), Endian::Little, args).unwrap();
+/// assert_eq!(text.0.0, "hi");
+/// ```
+#[derive(Debug, Default, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct Utf16String(pub String);
+
+fn read_code_unit<R: Read + ?Sized>(
+    reader: &mut R, endian: Endian, type_name: &'static str,
+) -> Result<u16, DecodeError> {
+    let mut buffer = [0; 2];
+    reader.read_exact(&mut buffer).map_err(|err| DecodeError::IncompleteData(type_name, err))?;
+    Ok(u16::from_bytes(buffer, endian))
+}
+
+fn string_from_utf16(units: &[u16]) -> Result<String, DecodeError> {
+    let mut valid_prefix = String::new();
+    for c in char::decode_utf16(units.iter().copied()) {
+        match c {
+            Ok(c) => valid_prefix.push(c),
+            Err(err) => return Err(DecodeError::DecodeUtf16Error {
+                valid_prefix: valid_prefix.into_boxed_str(),
+                unpaired_surrogate: err.unpaired_surrogate(),
+            }),
+        }
+    }
+    Ok(valid_prefix)
+}
+
+fn write_code_units<W: Write + ?Sized>(
+    writer: &mut W, units: impl Iterator<Item = u16>, endian: Endian,
+) -> Result<(), EncodeError> {
+    let bytes = units.flat_map(|unit| unit.to_bytes(endian)).collect::<Vec<_>>();
+    writer.write_all(&bytes).map_err(EncodeError::from)
+}
+
+impl Context<dir::Read> for Utf16String {
+    type EndianContext = Endian;
+    type ArgsBuilder = Utf16StrArgsBuilder<Required>;
+    fn args_builder() -> Utf16StrArgsBuilder<Required> { Utf16StrArgsBuilder::default() }
+}
+
+impl Decode<Utf16StrArgs> for Utf16String {
+    fn decode_with<R: Read + ?Sized>(reader: &mut R, endian: Endian, args: Utf16StrArgs) -> Result<Self, DecodeError> {
+        let units = (0..args.count)
+            .map(|_| read_code_unit(reader, endian, "Utf16String"))
+            .collect::<Result<Vec<_>, _>>()?;
+        string_from_utf16(&units).map(Utf16String)
+    }
+}
+
+decode_borrowed_by_decode!(Utf16String: Utf16StrArgs);
+
+impl Context<dir::Write> for Utf16String {
+    type EndianContext = Endian;
     type ArgsBuilder = NoArgs;
     fn args_builder() -> NoArgs { NoArgs }
 }
 
-impl Encode for str {
-    fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, _: NoEndian, _: ()) -> Result<(), EncodeError> {
-        writer.write_all(self.as_bytes()).map_err(EncodeError::from)
+impl Encode for Utf16String {
+    fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, endian: Endian, _: ()) -> Result<(), EncodeError> {
+        write_code_units(writer, self.0.encode_utf16(), endian)
+    }
+    fn encoded_size_with(&self, _: Endian, _: ()) -> Result<u64, EncodeError> {
+        Ok(2 * self.0.encode_utf16().count() as u64)
+    }
+}
+
+impl<Dir: Direction> Context<Dir> for NullTerminated<Utf16String> {
+    type EndianContext = Endian;
+    type ArgsBuilder = NullTerminatedArgsBuilder;
+    fn args_builder() -> Self::ArgsBuilder { NullTerminatedArgsBuilder::default() }
+}
+
+impl Decode<NullTerminatedArgs> for NullTerminated<Utf16String> {
+    fn decode_with<R: Read + ?Sized>(reader: &mut R, endian: Endian, args: NullTerminatedArgs) -> Result<Self, DecodeError> {
+        let mut units = Vec::new();
+        loop {
+            let unit = read_code_unit(reader, endian, "NullTerminated")?;
+            if unit == 0 { break; }
+            if args.max_length.is_some_and(|max_length| units.len() >= max_length) {
+                return Err(DecodeError::InvalidData("NullTerminated"));
+            }
+            units.push(unit);
+        }
+        string_from_utf16(&units).map(|s| NullTerminated(Utf16String(s)))
+    }
+}
+
+decode_borrowed_by_decode!(NullTerminated<Utf16String>: NullTerminatedArgs);
+
+impl Encode<NullTerminatedArgs> for NullTerminated<Utf16String> {
+    fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, endian: Endian, args: NullTerminatedArgs) -> Result<(), EncodeError> {
+        self.encoded_size_with(endian, args)?;
+        write_code_units(writer, self.0.0.encode_utf16().chain([0]), endian)
+    }
+    fn encoded_size_with(&self, _: Endian, args: NullTerminatedArgs) -> Result<u64, EncodeError> {
+        if self.0.0.contains('\0') {
+            return Err(EncodeError::InvalidData("NullTerminated"));
+        }
+        let length = self.0.0.encode_utf16().count();
+        if args.max_length.is_some_and(|max_length| length > max_length) {
+            return Err(EncodeError::InvalidArgument("NullTerminated", "string too long"));
+        }
+        Ok(2 * length as u64 + 2)
+    }
+}
+
+impl Context<dir::Read> for &[u8] {
+    type EndianContext = NoEndian;
+    type ArgsBuilder = VecArgsBuilder<Required>;
+    fn args_builder() -> Self::ArgsBuilder { VecArgsBuilder::default() }
+}
+
+impl<'de, Args: ExactSizeIterator<Item = ()>> DecodeBorrowed<'de, VecArgs<Args>> for &'de [u8] {
+    fn decode_borrowed_with(reader: &mut PositionTracker<&'de [u8]>, _: NoEndian, args: VecArgs<Args>) -> Result<Self, DecodeError> {
+        reader.borrow_bytes(args.element_args.len()).map_err(|err| DecodeError::IncompleteData("&[u8]", err))
+    }
+}
+
+impl Context<dir::Read> for &str {
+    type EndianContext = NoEndian;
+    type ArgsBuilder = StrArgsBuilder<Required>;
+    fn args_builder() -> StrArgsBuilder<Required> { StrArgsBuilder::default() }
+}
+
+/// Only UTF-8 strings, or strings consisting of ASCII characters in other encodings, can be borrowed
+/// from the input; for other strings, decode a [`String`] instead.
+impl<'de> DecodeBorrowed<'de, StrArgs> for &'de str {
+    fn decode_borrowed_with(reader: &mut PositionTracker<&'de [u8]>, _: NoEndian, args: StrArgs) -> Result<Self, DecodeError> {
+        let bytes = reader.borrow_bytes(args.count).map_err(|err| DecodeError::IncompleteData("&str", err))?;
+        match args.encoding {
+            Encoding::Utf8 => std::str::from_utf8(bytes).map_err(|err| DecodeError::from_utf8_error(bytes, err)),
+            _ if bytes.is_ascii() => Ok(std::str::from_utf8(bytes).unwrap()),
+            _ => Err(DecodeError::InvalidData("&str")),
+        }
+    }
+}
+
+/// Borrowed slice of [`PlainData`] elements, without any alignment requirement.
+///
+/// The elements are kept as raw bytes in the input, and converted using the endianness given when
+/// decoding, every time they are accessed.
+/// ```
+/// # use bin_data::context::{ArgsBuilderFinished, Context, Endian};
+/// # use bin_data::data::{DecodeBorrowed, UnalignedSlice};
+/// # use bin_data::stream::{dir, PositionTracker};
+/// let input = [0x00, 0x01, 0x02, 0x03];
+/// let mut reader = PositionTracker::new(&input[1..]);
+/// let args = <UnalignedSlice<u16> as Context<dir::Read>>::args_builder().count(1).finish();
+/// let slice = UnalignedSlice::<u16>::decode_borrowed_with(&mut reader, Endian::Big, args).unwrap();
+/// assert_eq!(slice.get(0), Some(0x0102));
+/// assert_eq!(slice.as_bytes().as_ptr(), input[1..].as_ptr());
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct UnalignedSlice<'de, T> {
+    bytes: &'de [u8],
+    endian: Endian,
+    _element: PhantomData<T>,
+}
+
+impl<'de, T: PlainData> UnalignedSlice<'de, T> {
+    fn element_size() -> usize { T::RawBytes::default().as_ref().len() }
+
+    /// Number of elements in this slice.
+    pub fn len(&self) -> usize { self.bytes.len() / Self::element_size() }
+
+    /// Whether this slice contains no elements.
+    pub fn is_empty(&self) -> bool { self.bytes.is_empty() }
+
+    /// Endianness of the elements.
+    pub fn endian(&self) -> Endian { self.endian }
+
+    /// Raw bytes of the elements, borrowed from the input.
+    pub fn as_bytes(&self) -> &'de [u8] { self.bytes }
+
+    /// Get the element at `index`.
+    pub fn get(&self, index: usize) -> Option<T> {
+        let size = Self::element_size();
+        let bytes = self.bytes.get(index * size..(index + 1) * size)?;
+        let mut buffer = T::RawBytes::default();
+        buffer.as_mut().copy_from_slice(bytes);
+        Some(T::from_bytes(buffer, self.endian))
+    }
+
+    /// Iterate over the elements.
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        (0..self.len()).map(|index| self.get(index).unwrap())
+    }
+}
+
+impl<'de, T> Context<dir::Read> for UnalignedSlice<'de, T> {
+    type EndianContext = Endian;
+    type ArgsBuilder = VecArgsBuilder<Required>;
+    fn args_builder() -> Self::ArgsBuilder { VecArgsBuilder::default() }
+}
+
+impl<'de, T: PlainData, Args: ExactSizeIterator<Item = ()>> DecodeBorrowed<'de, VecArgs<Args>> for UnalignedSlice<'de, T> {
+    fn decode_borrowed_with(reader: &mut PositionTracker<&'de [u8]>, endian: Endian, args: VecArgs<Args>) -> Result<Self, DecodeError> {
+        let count = args.element_args.len() * Self::element_size();
+        let bytes = reader.borrow_bytes(count).map_err(|err| DecodeError::IncompleteData("UnalignedSlice", err))?;
+        Ok(UnalignedSlice { bytes, endian, _element: PhantomData })
+    }
+}
+
+impl<'de, T> Context<dir::Write> for UnalignedSlice<'de, T> {
+    type EndianContext = Endian;
+    type ArgsBuilder = NoArgs;
+    fn args_builder() -> NoArgs { NoArgs }
+}
+
+impl<'de, T: PlainData> Encode for UnalignedSlice<'de, T> {
+    fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, endian: Endian, _: ()) -> Result<(), EncodeError> {
+        if endian == self.endian {
+            return writer.write_all(self.bytes).map_err(EncodeError::from);
+        }
+        self.iter().try_for_each(|x| plain_data_encode_with(&x, writer, endian))
+    }
+    fn encoded_size_with(&self, _: Endian, _: ()) -> Result<u64, EncodeError> {
+        Ok(self.bytes.len() as u64)
     }
 }
@@ -8,4 +8,4 @@ pub mod stream;
 pub mod data;
 
 #[cfg(feature = "macros")]
-pub use bin_data_macros::bin_data;
+pub use bin_data_macros::{bin_data, BinData};
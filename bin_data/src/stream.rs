@@ -1,11 +1,14 @@
 //! Input and output streams for binary data.
 
+use std::collections::VecDeque;
 use std::error::Error;
+use std::fmt::{Display, Formatter};
 use std::io::{Read, Write};
+use std::str::Utf8Error;
 use std::string::FromUtf8Error;
 use thiserror::Error;
 use crate::data::{Be, Le, PlainData};
-use crate::context::Endian;
+use crate::context::{Encoding, Endian};
 
 macro_rules! declare_type_enum {
     ($(#[$enum_meta:meta])*
@@ -67,6 +70,11 @@ impl<T: PlainData> IntoMagic for Be<T> {
     fn into_magic(self) -> Self::MagicRepr { self.0.to_bytes(Endian::Big) }
 }
 
+/// Used in automatically generated code to calculate the size of a magic sequence.
+pub const fn magic_size<M: IntoMagic>(_magic: &M) -> usize {
+    std::mem::size_of::<M::MagicRepr>()
+}
+
 /// Extensions shared by input and output streams.
 pub trait Stream<Dir: Direction> {
     /// Error returned by stream operations.
@@ -75,6 +83,57 @@ pub trait Stream<Dir: Direction> {
     fn magic<M: IntoMagic>(&mut self, magic: M) -> Result<(), Self::StreamError>;
     /// Declares there is a padding in the binary data.
     fn pad(&mut self, n: usize) -> Result<(), Self::StreamError>;
+    /// Error for an invalid stream operation, e.g., aligning from a position not yet reached.
+    fn invalid_data(what: &'static str) -> Self::StreamError;
+}
+
+/// Extensions for streams aware of their current position.
+///
+/// Alignment is relative to the start of the stream by default, i.e., where the outermost
+/// [`Decode::decode`] or [`Encode::encode`] started (or [`Decode::decode_with`] and
+/// [`Encode::encode_with`], which start tracking the position where they are called). In
+/// `bin_data!`, the position where the current `struct` or `enum` started is available as
+/// `struct_start`:
+/// ```
+/// # use bin_data::data::{Decode, Encode};
+/// # use bin_data_macros::bin_data;
+/// bin_data! {
+///     #[derive(Debug, Eq, PartialEq)]
+///     #[bin_data(endian = "little")]
+///     pub struct Chunk {
+///         pub kind: u8,
+///         @align(4),
+///         pub value: u8,
+///         @align_from(struct_start, 2),
+///     }
+/// }
+/// let mut buffer = Vec::new();
+/// Chunk { kind: 1, value: 2 }.encode(&mut buffer).unwrap();
+/// assert_eq!(buffer, [1, 0, 0, 0, 2, 0]);
+/// assert_eq!(Chunk::decode(&mut buffer.as_slice()).unwrap(), Chunk { kind: 1, value: 2 });
+/// ```
+///
+/// [`Decode::decode`]: crate::data::Decode::decode
+/// [`Decode::decode_with`]: crate::data::Decode::decode_with
+/// [`Encode::encode`]: crate::data::Encode::encode
+/// [`Encode::encode_with`]: crate::data::Encode::encode_with
+pub trait StreamPosition<Dir: Direction>: Stream<Dir> {
+    /// Number of bytes read or written so far.
+    fn position(&self) -> u64;
+    /// Skip or write just enough bytes to reach the next multiple of `n`, relative to the start
+    /// of the stream. Alignment of `0` or `1` is a no-op.
+    fn align(&mut self, n: usize) -> Result<(), Self::StreamError> {
+        self.align_from(0, n)
+    }
+    /// Skip or write just enough bytes to reach the next multiple of `n`, relative to `origin`.
+    /// Alignment of `0` or `1` is a no-op. Fails if `origin` is past the current position.
+    fn align_from(&mut self, origin: u64, n: usize) -> Result<(), Self::StreamError> {
+        let offset = self.position().checked_sub(origin).ok_or_else(|| Self::invalid_data("alignment origin"))?;
+        match offset % (n.max(1) as u64) {
+            0 => Ok(()),
+            misalignment => self.pad(n - misalignment as usize),
+        }
+    }
 }
 
 /// Decoding errors.
@@ -102,21 +161,63 @@ pub enum DecodeError {
         /// The invalid bytes coming after the valid prefix.
         invalid_bytes: Box<[u8]>,
     },
+    /// Cannot decode UTF-16 strings.
+    #[error("invalid UTF-16: found unpaired surrogate '{unpaired_surrogate:#06X}', after successfully decoding '{valid_prefix}'")]
+    DecodeUtf16Error {
+        /// The string is valid until this point.
+        valid_prefix: Box<str>,
+        /// The unpaired surrogate coming after the valid prefix.
+        unpaired_surrogate: u16,
+    },
     /// Superfluous bytes after decoding finished. EOF expected.
     #[error("input stream not exhausted, remaining bytes: {0:?}")]
     SuperfluousBytes(Box<[u8]>),
+    /// Assertion from an `@assert` directive does not hold.
+    #[error("assertion failed: {message} (`{expression}`)")]
+    AssertionFailed {
+        /// Message given in the directive.
+        message: &'static str,
+        /// Source text of the asserted expression.
+        expression: &'static str,
+    },
+    /// Error at some position in the input stream.
+    #[error("at byte offset {offset}")]
+    AtOffset {
+        /// Byte offset of the failing read operation, relative to where decoding started.
+        offset: u64,
+        /// The original error.
+        #[source]
+        source: Box<DecodeError>,
+    },
+    /// Error when decoding some nested field or element.
+    #[error("failed to decode '{path}'{}", OffsetSuffix(*.offset))]
+    Nested {
+        /// Where the error occurred.
+        path: FieldPath,
+        /// Byte offset of the failing read operation, relative to where decoding started, if
+        /// recorded.
+        offset: Option<u64>,
+        /// The original error.
+        #[source]
+        source: Box<DecodeError>,
+    },
+}
+
+/// Byte offset appended to the message of [`DecodeError::Nested`], if any.
+struct OffsetSuffix(Option<u64>);
+
+impl Display for OffsetSuffix {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(offset) => write!(f, " at byte offset {offset}"),
+            None => Ok(()),
+        }
+    }
 }
 
 impl From<FromUtf8Error> for DecodeError {
     fn from(err: FromUtf8Error) -> Self {
-        let utf8_error = err.utf8_error();
-        let valid_up_to = utf8_error.valid_up_to();
-        let invalid_to = valid_up_to + utf8_error.error_len().unwrap_or(0);
-        let mut buffer = err.into_bytes();
-        buffer.truncate(invalid_to);
-        let invalid_bytes = buffer.split_off(valid_up_to).into_boxed_slice();
-        let valid_prefix = String::from_utf8(buffer).unwrap().into_boxed_str();
-        DecodeError::DecodeUtf8Error { valid_prefix, invalid_bytes }
+        DecodeError::from_utf8_error(err.as_bytes(), err.utf8_error())
     }
 }
 
@@ -141,6 +242,7 @@ impl<R: Read + ?Sized> Stream<dir::Read> for R {
         let mut buffer = vec![0_u8; n];
         self.read_exact(&mut buffer).map_err(|err| IncompleteData("padding", err))
     }
+    fn invalid_data(what: &'static str) -> DecodeError { DecodeError::InvalidData(what) }
 }
 
 /// Encoding errors.
@@ -155,6 +257,341 @@ pub enum EncodeError {
     /// I/O error in the output stream.
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
+    /// Character not representable in the text encoding.
+    #[error("character {character:?} cannot be encoded in {encoding:?}")]
+    UnmappableCharacter {
+        /// The unmappable character.
+        character: char,
+        /// The target text encoding.
+        encoding: Encoding,
+    },
+    /// Assertion from an `@assert` directive does not hold.
+    #[error("assertion failed: {message} (`{expression}`)")]
+    AssertionFailed {
+        /// Message given in the directive.
+        message: &'static str,
+        /// Source text of the asserted expression.
+        expression: &'static str,
+    },
+    /// Error when encoding some nested field or element.
+    #[error("failed to encode '{path}'")]
+    Nested {
+        /// Where the error occurred.
+        path: FieldPath,
+        /// The original error.
+        #[source]
+        source: Box<EncodeError>,
+    },
+}
+
+/// Path to a nested field or element, e.g., `Level.waves[3].zombies[0].kind`.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct FieldPath {
+    segments: VecDeque<PathSegment>,
+}
+
+/// Segment in a [`FieldPath`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PathSegment {
+    /// Some field in a type.
+    Field {
+        /// Name of the type containing this field.
+        type_name: &'static str,
+        /// Name of the field.
+        field: &'static str,
+    },
+    /// Some element in a sequence.
+    Index(usize),
+}
+
+impl FieldPath {
+    /// Segments in this path, from the outermost to the innermost.
+    pub fn segments(&self) -> impl Iterator<Item = &PathSegment> {
+        self.segments.iter()
+    }
+}
+
+impl Display for FieldPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(PathSegment::Field { type_name, .. }) = self.segments.front() {
+            f.write_str(type_name)?;
+        }
+        self.segments.iter().try_for_each(|segment| match segment {
+            PathSegment::Field { field, .. } => write!(f, ".{field}"),
+            PathSegment::Index(index) => write!(f, "[{index}]"),
+        })
+    }
+}
+
+macro_rules! impl_nested_error {
+    ($error:ident $(, $wrapper:ident)?) => {
+        impl $error {
+            /// Record that this error occurred in some field, as the outermost path segment.
+            pub fn in_field(self, type_name: &'static str, field: &'static str) -> Self {
+                self.nested(PathSegment::Field { type_name, field })
+            }
+
+            /// Record that this error occurred in some element, as the outermost path segment.
+            pub fn at_index(self, index: usize) -> Self {
+                self.nested(PathSegment::Index(index))
+            }
+
+            /// Path to the field or element where this error occurred, if recorded.
+            pub fn path(&self) -> Option<&FieldPath> {
+                match self {
+                    $error::Nested { path, .. } => Some(path),
+                    $($error::$wrapper { source, .. } => source.path(),)?
+                    _ => None,
+                }
+            }
+
+            /// The original error, without any context.
+            pub fn root_cause(&self) -> &Self {
+                match self {
+                    $error::Nested { source, .. } $(| $error::$wrapper { source, .. })? => source.root_cause(),
+                    err => err,
+                }
+            }
+        }
+    }
+}
+
+impl_nested_error!(DecodeError, AtOffset);
+impl_nested_error!(EncodeError);
+
+impl EncodeError {
+    fn nested(self, segment: PathSegment) -> Self {
+        match self {
+            EncodeError::Nested { mut path, source } => {
+                path.segments.push_front(segment);
+                EncodeError::Nested { path, source }
+            }
+            err => EncodeError::Nested {
+                path: FieldPath { segments: VecDeque::from([segment]) },
+                source: Box::new(err),
+            },
+        }
+    }
+}
+
+impl DecodeError {
+    /// The offset moves to the outermost path, so that it stays in the error message.
+    fn nested(self, segment: PathSegment) -> Self {
+        let (mut path, offset, source) = match self {
+            DecodeError::Nested { path, offset, source } => (path, offset, source),
+            DecodeError::AtOffset { offset, source } => (FieldPath::default(), Some(offset), source),
+            err => (FieldPath::default(), None, Box::new(err)),
+        };
+        path.segments.push_front(segment);
+        DecodeError::Nested { path, offset, source }
+    }
+
+    /// Build a [`DecodeError::DecodeUtf8Error`] from the bytes failed to be decoded as UTF-8.
+    pub(crate) fn from_utf8_error(bytes: &[u8], err: Utf8Error) -> Self {
+        let valid_up_to = err.valid_up_to();
+        let invalid_to = valid_up_to + err.error_len().unwrap_or(0);
+        let (valid_prefix, invalid_bytes) = bytes[..invalid_to].split_at(valid_up_to);
+        let valid_prefix = std::str::from_utf8(valid_prefix).unwrap_or_default().into();
+        DecodeError::DecodeUtf8Error { valid_prefix, invalid_bytes: invalid_bytes.into() }
+    }
+
+    /// Record the byte offset in the input stream where this error occurred. Errors already
+    /// carrying an offset are returned unchanged. For errors in nested fields, the offset is
+    /// recorded along with the field path, and shown in the same message.
+    pub fn at_offset(self, offset: u64) -> Self {
+        match self {
+            err if err.offset().is_some() => err,
+            DecodeError::Nested { path, source, .. } => DecodeError::Nested { path, offset: Some(offset), source },
+            err => DecodeError::AtOffset { offset, source: Box::new(err) },
+        }
+    }
+
+    /// Byte offset in the input stream where this error occurred, if recorded.
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            DecodeError::AtOffset { offset, .. } => Some(*offset),
+            DecodeError::Nested { offset, .. } => *offset,
+            _ => None,
+        }
+    }
+}
+
+/// Stream wrapper tracking the number of bytes read or written, without requiring [`Seek`].
+///
+/// [`Decode::decode_tracked`] and [`Encode::encode_tracked`] work on tracked streams, so that the
+/// current position is available (see [`StreamPosition`]). [`Decode::decode`] and
+/// [`Encode::encode`] wrap the stream automatically, and [`Decode::decode`] records the position
+/// of the read operation during which decoding failed (see [`DecodeError::offset`]).
+/// When calling [`Decode::decode_tracked`] directly, use [`DecodeError::at_offset`]:
+///
+/// ```
+/// # use bin_data::context::Endian;
+/// # use bin_data::data::Decode;
+/// # use bin_data::stream::PositionTracker;
+/// let mut reader = PositionTracker::new([1_u8, 0, 0].as_ref());
+/// let first = u16::decode_tracked(&mut reader, Endian::Little, ()).unwrap();
+/// assert_eq!((first, reader.position()), (1, 2));
+/// let err = u16::decode_tracked(&mut reader, Endian::Little, ())
+///     .map_err(|err| err.at_offset(reader.last_read_position()))
+///     .unwrap_err();
+/// assert_eq!(err.offset(), Some(2));
+/// ```
+///
+/// [`Seek`]: std::io::Seek
+/// [`Decode::decode`]: crate::data::Decode::decode
+/// [`Decode::decode_tracked`]: crate::data::Decode::decode_tracked
+/// [`Encode::encode`]: crate::data::Encode::encode
+/// [`Encode::encode_tracked`]: crate::data::Encode::encode_tracked
+#[derive(Debug)]
+pub struct PositionTracker<S: ?Sized> {
+    position: u64,
+    last_read_position: u64,
+    /// Byte read ahead by [`at_end`](PositionTracker::at_end), not yet counted in the position.
+    peeked: Option<u8>,
+    stream: S,
+}
+
+impl<S> PositionTracker<S> {
+    /// Start tracking the position of `stream`, from offset zero.
+    pub fn new(stream: S) -> Self {
+        PositionTracker { position: 0, last_read_position: 0, peeked: None, stream }
+    }
+
+    /// Stop tracking and get back the underlying stream.
+    pub fn into_inner(self) -> S { self.stream }
+}
+
+impl<'de> PositionTracker<&'de [u8]> {
+    /// Borrow the next `n` bytes from the input slice, without copying.
+    ///
+    /// Fails if a byte was read ahead by [`at_end`](PositionTracker::at_end), because it is no
+    /// longer part of the slice.
+    pub fn borrow_bytes(&mut self, n: usize) -> std::io::Result<&'de [u8]> {
+        if self.peeked.is_some() {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "cannot borrow after reading ahead"));
+        }
+        self.last_read_position = self.position;
+        let (bytes, rest) = self.stream.split_at_checked(n)
+            .ok_or(std::io::ErrorKind::UnexpectedEof)?;
+        self.stream = rest;
+        self.position += n as u64;
+        Ok(bytes)
+    }
+
+    /// Check whether the input slice is exhausted, without reading ahead like
+    /// [`at_end`](PositionTracker::at_end), so that bytes can still be borrowed afterwards.
+    pub fn is_exhausted(&self) -> bool {
+        self.peeked.is_none() && self.stream.is_empty()
+    }
+}
+
+impl PositionTracker<std::io::Sink> {
+    /// Byte counter: discards everything written, only keeping track of the position.
+    pub fn counter() -> Self { PositionTracker::counter_at(0) }
+
+    /// Byte counter starting at `position`, for data whose layout depends on the position.
+    pub fn counter_at(position: u64) -> Self {
+        PositionTracker { position, last_read_position: position, peeked: None, stream: std::io::sink() }
+    }
+}
+
+impl<S: ?Sized> PositionTracker<S> {
+    /// Number of bytes read or written so far.
+    pub fn position(&self) -> u64 { self.position }
+
+    /// Position at the start of the most recent read operation.
+    pub fn last_read_position(&self) -> u64 { self.last_read_position }
+
+    fn counted(&mut self) -> Counted<'_, S> {
+        self.last_read_position = self.position;
+        Counted { position: &mut self.position, peeked: &mut self.peeked, stream: &mut self.stream }
+    }
+}
+
+impl<R: Read + ?Sized> PositionTracker<R> {
+    /// Check whether the input stream is exhausted, reading ahead at most one byte.
+    ///
+    /// The byte read ahead is kept, and returned by the next read operation. It can no longer be
+    /// borrowed from an input slice, see [`is_exhausted`](PositionTracker::is_exhausted) instead.
+    pub fn at_end(&mut self) -> std::io::Result<bool> {
+        if self.peeked.is_some() { return Ok(false); }
+        let mut byte = [0_u8];
+        loop {
+            match self.stream.read(&mut byte) {
+                Ok(0) => return Ok(true),
+                Ok(_) => {
+                    self.peeked = Some(byte[0]);
+                    return Ok(false);
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+/// Forwards to the stream, counting the bytes read or written.
+struct Counted<'a, S: ?Sized> {
+    position: &'a mut u64,
+    peeked: &'a mut Option<u8>,
+    stream: &'a mut S,
+}
+
+impl<R: Read + ?Sized> Read for Counted<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if let (Some(byte), Some(first)) = (*self.peeked, buf.first_mut()) {
+            *first = byte;
+            *self.peeked = None;
+            *self.position += 1;
+            return Ok(1);
+        }
+        let n = self.stream.read(buf)?;
+        *self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl<W: Write + ?Sized> Write for Counted<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.stream.write(buf)?;
+        *self.position += n as u64;
+        Ok(n)
+    }
+    fn flush(&mut self) -> std::io::Result<()> { self.stream.flush() }
+}
+
+// every composite read operation is one read operation in terms of the position
+impl<R: Read + ?Sized> Read for PositionTracker<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.counted().read(buf)
+    }
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> std::io::Result<usize> {
+        self.counted().read_to_end(buf)
+    }
+    fn read_to_string(&mut self, buf: &mut String) -> std::io::Result<usize> {
+        self.counted().read_to_string(buf)
+    }
+    fn read_exact(&mut self, buf: &mut [u8]) -> std::io::Result<()> {
+        self.counted().read_exact(buf)
+    }
+}
+
+impl<W: Write + ?Sized> Write for PositionTracker<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.counted().write(buf)
+    }
+    fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        self.counted().write_all(buf)
+    }
+    fn flush(&mut self) -> std::io::Result<()> { self.stream.flush() }
+}
+
+impl<R: Read + ?Sized> StreamPosition<dir::Read> for PositionTracker<R> {
+    fn position(&self) -> u64 { self.position }
+}
+
+impl<W: Write + ?Sized> StreamPosition<dir::Write> for PositionTracker<W> {
+    fn position(&self) -> u64 { self.position }
 }
 
 impl<W: Write + ?Sized> Stream<dir::Write> for W {
@@ -166,4 +603,5 @@ impl<W: Write + ?Sized> Stream<dir::Write> for W {
     fn pad(&mut self, n: usize) -> Result<(), EncodeError> {
         self.write_all(&vec![0_u8; n]).map_err(EncodeError::from)
     }
+    fn invalid_data(what: &'static str) -> EncodeError { EncodeError::InvalidData(what) }
 }
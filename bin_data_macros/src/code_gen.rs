@@ -1,25 +1,39 @@
+use std::collections::{HashMap, HashSet};
 use itertools::Itertools;
-use proc_macro2::{Span, TokenStream};
-use quote::{quote, quote_spanned};
-use syn::{Expr, LitStr, spanned::Spanned};
-use crate::input::{ArgFieldAssign, ArgFieldDecl, EndianConfig, Entry, Field, Input, KnownAttribute, WithToken};
+use proc_macro2::{Ident, Literal, Span, TokenStream, TokenTree};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::parse::{ParseStream, Parser};
+use syn::{Expr, ExprGroup, ExprLit, ExprParen, ExprUnary, GenericArgument, GenericParam, Generics, Lifetime, Lit, LitInt, LitStr, Member, PathArguments, PathSegment, Type, TypePath, UnOp, WhereClause, spanned::Spanned};
+use syn::punctuated::Punctuated;
+use syn::Token;
+use crate::input::{ArgFieldAssign, ArgFieldDecl, BitContainer, BitOrder, BitWidth, Body, Data, Directive, EndianConfig, Entry, Field, Input, KnownAttribute, Variant, WithToken};
 
-pub fn extract_struct(input: &Input, result: &mut TokenStream) {
+pub fn extract_type(input: &Input, result: &mut TokenStream) {
     let Input {
         known_attrs: _,
         attrs,
         vis,
-        struct_token,
         name,
         generics,
-        brace_token,
-        entries: _,
+        data,
     } = input;
-    result.extend(quote! { #(#attrs)* #vis #struct_token #name #generics });
-    brace_token.surround(result, |tokens| {
-        let fields = input.fields();
-        tokens.extend(quote! { #(#fields),* })
-    });
+    result.extend(quote! { #(#attrs)* #vis });
+    match data {
+        Data::Struct { struct_token, body, .. } if body.fields().next().is_none() => {
+            result.extend(quote! { #struct_token #name #generics; })
+        }
+        Data::Struct { struct_token, body: body @ Body::Named { .. }, .. } => {
+            result.extend(quote! { #struct_token #name #generics #body })
+        }
+        Data::Struct { struct_token, body, semi_token } => {
+            let semi_token = semi_token.unwrap_or_default();
+            result.extend(quote! { #struct_token #name #generics #body #semi_token })
+        }
+        Data::Enum { enum_token, brace_token, variants } => {
+            result.extend(quote! { #enum_token #name #generics });
+            brace_token.surround(result, |tokens| tokens.extend(quote! { #variants }));
+        }
+    }
 }
 
 #[derive(Default)]
@@ -27,6 +41,13 @@ pub struct ExtractedArgs<'a> {
     endian: Option<&'a WithToken<LitStr, EndianConfig>>,
     encode: Config<'a>,
     decode: Config<'a>,
+    tag_decl: Option<&'a Type>,
+    tag_assign: Option<&'a Expr>,
+    condition: Option<&'a Expr>,
+    bits: Option<&'a WithToken<LitInt, BitWidth>>,
+    container: Option<&'a WithToken<Ident, BitContainer>>,
+    bit_order: Option<&'a WithToken<LitStr, BitOrder>>,
+    unknown: Option<&'a Ident>,
     errors: TokenStream,
 }
 
@@ -46,7 +67,7 @@ impl Config<'_> {
     }
 }
 
-pub fn extract_args(known_attrs: &[KnownAttribute]) -> ExtractedArgs {
+pub fn extract_args(known_attrs: &[KnownAttribute]) -> ExtractedArgs<'_> {
     let mut args = ExtractedArgs::default();
     for attr in known_attrs {
         macro_rules! set {
@@ -63,6 +84,13 @@ pub fn extract_args(known_attrs: &[KnownAttribute]) -> ExtractedArgs {
             KnownAttribute::Endian(endian) => set!(args.errors, "endian", args.endian, endian),
             KnownAttribute::Encode(value) => set!(args.errors, "encode", args.encode.calculate, value),
             KnownAttribute::Decode(value) => set!(args.errors, "decode", args.decode.calculate, value),
+            KnownAttribute::TagDecl(tag) => set!(args.errors, "tag", args.tag_decl, tag),
+            KnownAttribute::TagAssign(tag) => set!(args.errors, "tag", args.tag_assign, tag),
+            KnownAttribute::If(condition) => set!(args.errors, "if", args.condition, condition),
+            KnownAttribute::Bits(bits) => set!(args.errors, "bits", args.bits, bits),
+            KnownAttribute::Container(container) => set!(args.errors, "container", args.container, container),
+            KnownAttribute::BitOrder(order) => set!(args.errors, "bit_order", args.bit_order, order),
+            KnownAttribute::Unknown(unknown) => set!(args.errors, "unknown", args.unknown, unknown),
             KnownAttribute::ArgsAssign { direction, fields, .. } => direction.dispatch(
                 &mut args.encode.args_assign,
                 &mut args.decode.args_assign,
@@ -75,9 +103,22 @@ pub fn extract_args(known_attrs: &[KnownAttribute]) -> ExtractedArgs {
             ),
         }
     }
+    if let (Some(container), None) = (args.container, args.bits) {
+        let msg = "`container` can only be used together with `bits`";
+        args.errors.extend(quote_spanned!(container.span() => compile_error!(#msg);));
+    }
     args
 }
 
+fn extract_body_args(body: &Body) -> Vec<Option<ExtractedArgs<'_>>> {
+    body.entries()
+        .map(|entry| match entry {
+            Entry::Directive(_) => None,
+            Entry::Field(field) => Some(extract_args(&field.known_attrs)),
+        })
+        .collect()
+}
+
 fn decide_endian(
     field_span: Span,
     local_endian: Option<&WithToken<LitStr, EndianConfig>>,
@@ -95,99 +136,913 @@ fn decide_endian(
     }
 }
 
+/// Where to find the tag for an `enum`.
+#[derive(Copy, Clone)]
+enum TagSource<'a> {
+    /// Tag is stored in the binary data just before the variant: `#[bin_data(tag: Type)]`.
+    Inline(&'a Type),
+    /// Tag is supplied by the parent: `#[bin_data(args { tag: Type })]`.
+    External(&'a Type),
+    /// No tag available for this direction, only allowed for encoding.
+    Missing,
+}
+
+impl<'a> TagSource<'a> {
+    fn new(args: &ExtractedArgs<'a>, config: &Config<'a>) -> Self {
+        match (args.tag_decl, config.args_decl.iter().find(|arg| arg.name == "tag")) {
+            (Some(tag_type), _) => TagSource::Inline(tag_type),
+            (None, Some(arg)) => TagSource::External(&arg.r#type),
+            (None, None) => TagSource::Missing,
+        }
+    }
+
+}
+
+fn check_enum_args(input: &Input, args: &ExtractedArgs, errors: &mut TokenStream) {
+    let mut args_decl = args.decode.args_decl.iter().chain(&args.encode.args_decl);
+    match (args.tag_decl, TagSource::new(args, &args.decode)) {
+        (Some(tag_type), _) if args_decl.any(|arg| arg.name == "tag") => {
+            let msg = "tag cannot be both inline and supplied as an argument";
+            errors.extend(quote_spanned!(tag_type.span() => compile_error!(#msg);));
+        }
+        (_, TagSource::Missing) => {
+            let msg = "`enum` requires a tag: use `#[bin_data(tag: Type)]` or `#[bin_data(args { tag: Type })]`";
+            errors.extend(quote_spanned!(input.name.span() => compile_error!(#msg);));
+        }
+        _ => {}
+    }
+    let Data::Enum { variants, .. } = &input.data else { return; };
+    let mut unknown_variants = variants.iter().filter(|variant| is_unknown(variant));
+    if let Some(variant) = unknown_variants.nth(1) {
+        let msg = "only one variant can be `unknown`";
+        errors.extend(quote_spanned!(variant.name.span() => compile_error!(#msg);));
+    }
+    for variant in variants.iter().filter(|variant| is_unknown(variant)) {
+        let variant_args = extract_args(&variant.known_attrs);
+        if let Some(tag) = variant_args.tag_assign.or(variant.discriminant.as_ref().map(|(_, value)| value)) {
+            let msg = "`unknown` variant cannot have a tag";
+            errors.extend(quote_spanned!(tag.span() => compile_error!(#msg);));
+        }
+        let single_field = match &variant.body {
+            Body::Unnamed { entries, .. } => entries.len() == 1 && entries.iter().all(|entry| entry.as_field().is_some()),
+            _ => false,
+        };
+        if !single_field {
+            let msg = "`unknown` variant must have exactly one positional field for the raw tag";
+            errors.extend(quote_spanned!(variant.name.span() => compile_error!(#msg);));
+        }
+    }
+    check_duplicate_tags(variants, errors);
+}
+
+/// Diagnose variants with the same tag, as far as the tags are known from literals.
+fn check_duplicate_tags(variants: &Punctuated<Variant, Token![,]>, errors: &mut TokenStream) {
+    let mut seen = HashMap::new();
+    let mut next = Some(0_i128);
+    for variant in variants.iter().filter(|variant| !is_unknown(variant)) {
+        let explicit = extract_args(&variant.known_attrs).tag_assign
+            .or(variant.discriminant.as_ref().map(|(_, value)| value));
+        let value = match explicit {
+            Some(expr) => literal_value(expr),
+            None => next,
+        };
+        next = value.and_then(|value| value.checked_add(1));
+        let Some(value) = value else { continue; };
+        if let Some(previous) = seen.insert(value, &variant.name) {
+            let msg = format!("tag `{}` is already used by variant `{}`", value, previous);
+            let span = explicit.map_or(variant.name.span(), |expr| expr.span());
+            errors.extend(quote_spanned!(span => compile_error!(#msg);));
+        }
+    }
+}
+
+/// Value of an integer or byte literal, possibly negated or parenthesised.
+fn literal_value(expr: &Expr) -> Option<i128> {
+    match expr {
+        Expr::Lit(ExprLit { lit: Lit::Int(lit), .. }) => lit.base10_parse().ok(),
+        Expr::Lit(ExprLit { lit: Lit::Byte(lit), .. }) => Some(lit.value().into()),
+        Expr::Unary(ExprUnary { op: UnOp::Neg(_), expr, .. }) => literal_value(expr)?.checked_neg(),
+        Expr::Paren(ExprParen { expr, .. }) | Expr::Group(ExprGroup { expr, .. }) => literal_value(expr),
+        _ => None,
+    }
+}
+
+/// Whether this variant captures unrecognised tags: `#[bin_data(unknown)]`.
+fn is_unknown(variant: &Variant) -> bool {
+    extract_args(&variant.known_attrs).unknown.is_some()
+}
+
+/// Arguments declared using `#[bin_data(args { ... })]`, for which we generate an arguments
+/// `struct` and a builder. If encoding and decoding share the same arguments, they are named
+/// `NameArgs` and `NameArgsBuilder`; otherwise, `NameDecodeArgs`, `NameEncodeArgs`, etc.
+struct DeclaredArgs<'a> {
+    shared: bool,
+    args_name: Ident,
+    builder_name: Ident,
+    fields: Vec<&'a ArgFieldDecl>,
+    /// Generic parameters of the input mentioned in the argument types.
+    generics: Generics,
+}
+
+impl<'a> DeclaredArgs<'a> {
+    fn new(input: &Input, args: &ExtractedArgs<'a>, config: &Config<'a>, direction: &str) -> Self {
+        let shared = args.decode.args_decl.len() == args.encode.args_decl.len()
+            && std::iter::zip(&args.decode.args_decl, &args.encode.args_decl)
+                .all(|(x, y)| std::ptr::eq(*x, *y));
+        let direction = if shared { "" } else { direction };
+        let args_name = format_ident!("{}{}Args", input.name, direction);
+        let builder_name = format_ident!("{}Builder", args_name);
+        let fields = config.args_decl.clone();
+        let generics = mentioned_generics(&input.generics, fields.iter().map(|arg| &arg.r#type));
+        DeclaredArgs { shared, args_name, builder_name, fields, generics }
+    }
+
+    /// Generic arguments for the parameters in `generics`, without the angle brackets.
+    fn generic_args(&self) -> Vec<TokenStream> {
+        self.generics.params.iter()
+            .map(|param| match param {
+                GenericParam::Lifetime(param) => param.lifetime.to_token_stream(),
+                GenericParam::Type(param) => param.ident.to_token_stream(),
+                GenericParam::Const(param) => param.ident.to_token_stream(),
+            })
+            .collect()
+    }
+
+    /// Returns the builder type, the arguments type, the initial builder, and the statement for
+    /// bringing the arguments into scope.
+    fn usage(&self) -> (TokenStream, TokenStream, TokenStream, TokenStream) {
+        if self.fields.is_empty() {
+            let no_args = quote!(::bin_data::context::NoArgs);
+            return (no_args.clone(), quote!(()), no_args, TokenStream::new());
+        }
+        let DeclaredArgs { args_name, builder_name, fields, .. } = self;
+        let generic_args = self.generic_args();
+        let (_, type_generics, _) = self.generics.split_for_impl();
+        let names = fields.iter().map(|arg| &arg.name).collect_vec();
+        let states = fields.iter().map(|arg| match &arg.default_value {
+            None => quote!(::bin_data::context::Required),
+            Some(_) => {
+                let r#type = &arg.r#type;
+                quote!(::bin_data::context::Provided<#r#type>)
+            }
+        });
+        let init = fields.iter().map(|arg| match &arg.default_value {
+            None => quote!(::bin_data::context::Required),
+            Some(value) => quote!(::bin_data::context::Provided(#value)),
+        });
+        (
+            quote!(#builder_name<#(#generic_args,)* #(#states),*>),
+            quote!(#args_name #type_generics),
+            quote!(#builder_name { #(#names: #init,)* __phantom: ::core::marker::PhantomData }),
+            quote! {
+                #[allow(unused_variables)]
+                let #args_name { #(#names),* } = args;
+            },
+        )
+    }
+
+    fn definitions(&self, input: &Input, purpose: &str) -> TokenStream {
+        if self.fields.is_empty() { return TokenStream::new(); }
+        let DeclaredArgs { args_name, builder_name, fields, generics, .. } = self;
+        let vis = &input.vis;
+        let names = fields.iter().map(|arg| &arg.name).collect_vec();
+        let types = fields.iter().map(|arg| &arg.r#type).collect_vec();
+        let params = (0..fields.len()).map(|k| format_ident!("__A{}", k)).collect_vec();
+        let generic_params = generics.params.iter().collect_vec();
+        let generic_args = self.generic_args();
+        let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+        let predicates = where_clause.iter().flat_map(|clause| &clause.predicates).collect_vec();
+        // the builder only stores the arguments, but its type still depends on all the parameters
+        let phantom = generics.params.iter().filter_map(|param| match param {
+            GenericParam::Lifetime(param) => {
+                let lifetime = &param.lifetime;
+                Some(quote!(&#lifetime ()))
+            }
+            GenericParam::Type(param) => Some(param.ident.to_token_stream()),
+            GenericParam::Const(_) => None,
+        });
+        let args_doc = format!("Arguments for {} [`{}`].", purpose, input.name);
+        let builder_doc = format!("Named arguments builder for [`{}`].", args_name);
+        let field_docs = names.iter().map(|name| format!("Argument `{}`.", name));
+        let setter_docs = names.iter().map(|name| format!("Specify the argument `{}`.", name));
+        let setters = fields.iter().enumerate().map(|(k, arg)| {
+            let name = &arg.name;
+            let r#type = &arg.r#type;
+            let mut result_params = params.iter().map(|param| quote!(#param)).collect_vec();
+            result_params[k] = quote!(::bin_data::context::Provided<#r#type>);
+            let others = names.iter().filter(|&&other| other != name);
+            quote! {
+                #vis fn #name(self, #name: #r#type) -> #builder_name<#(#generic_args,)* #(#result_params),*> {
+                    #builder_name {
+                        #name: ::bin_data::context::Provided(#name),
+                        #(#others: self.#others,)*
+                        __phantom: ::core::marker::PhantomData,
+                    }
+                }
+            }
+        });
+        let args_name_literal = args_name.to_string();
+        let name_literals = names.iter().map(|name| name.to_string());
+        // `for<'__b>` defers the bounds to the use site, so that arguments of types without
+        // `Debug` or `Clone` are still accepted
+        quote! {
+            #[doc = #args_doc]
+            #vis struct #args_name #impl_generics #where_clause {
+                #(#[doc = #field_docs] #vis #names: #types,)*
+            }
+            impl #impl_generics ::core::fmt::Debug for #args_name #type_generics
+                where #(#predicates,)* #(for<'__b> #types: ::core::fmt::Debug,)* {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    f.debug_struct(#args_name_literal)
+                        #(.field(#name_literals, &self.#names))*
+                        .finish()
+                }
+            }
+            impl #impl_generics ::core::clone::Clone for #args_name #type_generics
+                where #(#predicates,)* #(for<'__b> #types: ::core::clone::Clone,)* {
+                fn clone(&self) -> Self {
+                    #args_name { #(#names: ::core::clone::Clone::clone(&self.#names)),* }
+                }
+            }
+            #[doc = #builder_doc]
+            #[derive(Debug, Clone)]
+            #vis struct #builder_name<#(#generic_params,)* #(#params),*> #where_clause {
+                #(#names: #params,)*
+                __phantom: ::core::marker::PhantomData<fn() -> (#(#phantom,)*)>,
+            }
+            impl<#(#generic_params,)* #(#params),*> #builder_name<#(#generic_args,)* #(#params),*> #where_clause {
+                #(#[doc = #setter_docs] #setters)*
+            }
+            impl #impl_generics ::bin_data::context::ArgsBuilderFinished
+                for #builder_name<#(#generic_args,)* #(::bin_data::context::Provided<#types>),*> #where_clause {
+                type Output = #args_name #type_generics;
+                fn finish(self) -> Self::Output {
+                    #args_name { #(#names: self.#names.0),* }
+                }
+            }
+        }
+    }
+}
+
+/// The generic parameters mentioned in `types`, and the predicates only involving those.
+fn mentioned_generics<'a>(generics: &Generics, types: impl Iterator<Item = &'a Type>) -> Generics {
+    let mut mentioned = HashSet::new();
+    types.for_each(|r#type| collect_idents(r#type.to_token_stream(), &mut mentioned));
+    let param_ident = |param: &GenericParam| match param {
+        GenericParam::Lifetime(param) => param.lifetime.ident.clone(),
+        GenericParam::Type(param) => param.ident.clone(),
+        GenericParam::Const(param) => param.ident.clone(),
+    };
+    let (kept, dropped): (Vec<_>, Vec<_>) = generics.params.iter()
+        .partition(|param| mentioned.contains(&param_ident(param)));
+    let dropped: HashSet<_> = dropped.into_iter().map(param_ident).collect();
+    let where_clause = generics.where_clause.as_ref().map(|clause| WhereClause {
+        where_token: clause.where_token,
+        predicates: clause.predicates.iter()
+            .filter(|predicate| {
+                let mut idents = HashSet::new();
+                collect_idents(predicate.to_token_stream(), &mut idents);
+                idents.is_disjoint(&dropped)
+            })
+            .cloned()
+            .collect(),
+    });
+    Generics { params: kept.into_iter().cloned().collect(), where_clause, ..generics.clone() }
+}
+
+/// Tag values for each variant: explicitly specified by `#[bin_data(tag = ...)]`, or else the
+/// discriminant of that variant, or else the previous tag plus one (starting at zero). The
+/// `unknown` variant has no tag value of its own, and is skipped.
+fn variant_tags(variants: &Punctuated<Variant, Token![,]>) -> Vec<Option<TokenStream>> {
+    let mut last_explicit = None;
+    let mut offset = 0_usize;
+    variants.iter()
+        .map(|variant| {
+            if is_unknown(variant) { return None; }
+            let explicit = extract_args(&variant.known_attrs).tag_assign
+                .or(variant.discriminant.as_ref().map(|(_, value)| value));
+            if let Some(value) = explicit {
+                last_explicit = Some(value);
+                offset = 1;
+                return Some(quote!(#value));
+            }
+            let k = Literal::usize_unsuffixed(offset);
+            offset += 1;
+            Some(match last_explicit {
+                None => quote!(#k),
+                Some(last) => quote!((#last) + #k),
+            })
+        })
+        .collect()
+}
+
+/// Settings of the enclosing type, shared by all entries in a body.
+struct BodyConfig {
+    endian: EndianConfig,
+    bit_order: BitOrder,
+    /// Type name (or `Type::Variant`) in error paths.
+    type_name: LitStr,
+    /// Lifetime of the input for borrowed decoding, i.e., the first lifetime parameter.
+    borrowed: Option<Lifetime>,
+}
+
+impl BodyConfig {
+    fn new(input: &Input, args: &ExtractedArgs) -> Self {
+        BodyConfig {
+            endian: args.endian.map_or(EndianConfig::None, |t| t.value),
+            bit_order: args.bit_order.map_or(BitOrder::default(), |t| t.value),
+            type_name: type_name_literal(input),
+            borrowed: input.generics.lifetimes().next().map(|param| param.lifetime.clone()),
+        }
+    }
+
+    fn for_variant(&self, variant: &Variant) -> Self {
+        let type_name = format!("{}::{}", self.type_name.value(), variant.name);
+        BodyConfig {
+            type_name: LitStr::new(&type_name, variant.name.span()),
+            borrowed: self.borrowed.clone(),
+            ..*self
+        }
+    }
+
+    /// Decode a value of `type` from `reader`, borrowing from the input if possible.
+    fn decode(&self, span: Span, r#type: &Type, endian: &TokenStream, args: TokenStream) -> TokenStream {
+        match &self.borrowed {
+            None => quote_spanned!(span => <#r#type>::decode_tracked(reader, #endian, #args)),
+            Some(lifetime) => quote_spanned! { span =>
+                <#r#type as ::bin_data::data::DecodeBorrowed<#lifetime, _>>::decode_borrowed_with(reader, #endian, #args)
+            },
+        }
+    }
+
+    /// Record the field in the path of errors.
+    fn in_field(&self, field: &Field) -> TokenStream {
+        let type_name = &self.type_name;
+        let field_name = field_name_literal(field);
+        quote_spanned!(field.name.span() => .map_err(|err| err.in_field(#type_name, #field_name)))
+    }
+}
+
+fn field_name_literal(field: &Field) -> LitStr {
+    match &field.member {
+        Member::Named(name) => LitStr::new(&name.to_string(), name.span()),
+        Member::Unnamed(index) => LitStr::new(&index.index.to_string(), index.span),
+    }
+}
+
+fn type_name_literal(input: &Input) -> LitStr {
+    LitStr::new(&input.name.to_string(), input.name.span())
+}
+
 fn decode_entry(
-    global_endian: EndianConfig,
+    config: &BodyConfig,
+    body: &Body,
     entry: &Entry,
     args: &Option<ExtractedArgs>,
 ) -> TokenStream {
     match entry {
+        Entry::Directive(directive) if directive.directive == "assert" => assertion(body, entry, directive, true),
         Entry::Directive(directive) => quote!(reader.#directive?;),
-        Entry::Field(Field { name, r#type, .. }) => {
+        Entry::Field(field @ Field { name, r#type, .. }) => {
+            let in_field = config.in_field(field);
             let args = args.as_ref().unwrap();
+            let errors = &args.errors;
             let arg_setters = args.decode.arg_setters();
-            let endian = decide_endian(name.span(), args.endian, global_endian);
-            match args.decode.calculate {
-                Some(decode) => quote!(let #name: #r#type = #decode;),
-                None => quote_spanned! { name.span() =>
-                    let #name: #r#type = <#r#type>::decode_with(
-                        reader, #endian,
-                        ArgsBuilderFinished::finish(
-                            <#r#type as Context<dir::Read>>::args_builder() #arg_setters
-                        ),
-                    )?;
+            let endian = decide_endian(name.span(), args.endian, config.endian);
+            let decode = |r#type: &Type| {
+                let args = quote_spanned! { name.span() =>
+                    ArgsBuilderFinished::finish(<#r#type as Context<dir::Read>>::args_builder() #arg_setters)
+                };
+                let decode = config.decode(name.span(), r#type, &endian, args);
+                quote_spanned!(name.span() => #decode #in_field?)
+            };
+            match (args.decode.calculate, args.condition) {
+                (Some(decode), None) => quote!(#errors let #name: #r#type = #decode;),
+                (None, None) => {
+                    let value = decode(r#type);
+                    quote!(#errors let #name: #r#type = #value;)
+                }
+                (Some(decode), Some(_)) => quote_spanned! { decode.span() =>
+                    #errors
+                    let #name: #r#type = compile_error!("`if` cannot be used together with `decode`");
                 },
+                (None, Some(condition)) => {
+                    let value = match option_inner(r#type) {
+                        Ok(inner) => decode(inner),
+                        Err(err) => return quote!(#errors let #name: #r#type = #err;),
+                    };
+                    let bindings = bind_mentioned(body, entry, condition, true);
+                    let present = Ident::new("present", Span::mixed_site());
+                    quote_spanned! { name.span() =>
+                        #errors
+                        let #present = { #bindings ::core::convert::identity::<bool>(#condition) };
+                        let #name: #r#type = if #present {
+                            ::core::option::Option::Some(#value)
+                        } else {
+                            ::core::option::Option::None
+                        };
+                    }
+                }
             }
         }
     }
 }
 
-pub fn impl_decode(
-    input: &Input,
-    args: &ExtractedArgs,
-    field_args: &[Option<ExtractedArgs>],
-    result: &mut TokenStream,
-) {
+/// Extract `T` from `Option<T>`, for conditional fields.
+fn option_inner(r#type: &Type) -> Result<&Type, TokenStream> {
+    if let Type::Path(TypePath { qself: None, path }) = r#type {
+        if let Some(PathSegment { ident, arguments: PathArguments::AngleBracketed(args) }) = path.segments.last() {
+            if let (true, Some(GenericArgument::Type(inner)), 1) = (ident == "Option", args.args.first(), args.args.len()) {
+                return Ok(inner);
+            }
+        }
+    }
+    Err(quote_spanned!(r#type.span() => compile_error!("conditional field must be of type `Option<T>`")))
+}
+
+fn collect_idents(tokens: TokenStream, idents: &mut HashSet<Ident>) {
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) => { idents.insert(ident); }
+            TokenTree::Group(group) => collect_idents(group.stream(), idents),
+            _ => {}
+        }
+    }
+}
+
+/// Rebind the previous fields mentioned in a condition or an assertion, so that it sees all of
+/// them by reference, both when decoding and encoding.
+fn bind_mentioned(body: &Body, entry: &Entry, expr: &impl ToTokens, decoding: bool) -> TokenStream {
+    let mut mentioned = HashSet::new();
+    collect_idents(expr.to_token_stream(), &mut mentioned);
+    let bindings = body.entries()
+        .take_while(|previous| !std::ptr::eq(*previous, entry))
+        .filter_map(|previous| match previous {
+            Entry::Field(field) if mentioned.contains(&field.name) => Some(field),
+            _ => None,
+        })
+        .map(|Field { name, r#type, .. }| match decoding {
+            true => quote!(#[allow(unused_variables)] let #name = &#name;),
+            // fields are references, but temporaries are values
+            false => quote! {
+                #[allow(unused_variables)]
+                let #name: &#r#type = ::core::borrow::Borrow::<#r#type>::borrow(&#name);
+            },
+        });
+    quote!(#(#bindings)*)
+}
+
+/// `@assert(expr, "message")`: fail with `AssertionFailed` unless `expr` holds.
+fn assertion(body: &Body, entry: &Entry, directive: &Directive, decoding: bool) -> TokenStream {
+    let parser = |input: ParseStream| {
+        let expr: Expr = input.parse()?;
+        let _: Token![,] = input.parse()?;
+        let message: LitStr = input.parse()?;
+        let _: Option<Token![,]> = input.parse()?;
+        Ok((expr, message))
+    };
+    let (expr, message) = match parser.parse2(directive.arguments.clone()) {
+        Ok(parsed) => parsed,
+        Err(err) => return err.into_compile_error(),
+    };
+    let bindings = bind_mentioned(body, entry, &expr, decoding);
+    let expression = LitStr::new(&expression_text(directive, &expr, &message), expr.span());
+    let error = match decoding {
+        true => quote!(DecodeError),
+        false => quote!(EncodeError),
+    };
+    quote_spanned! { directive.directive.span() =>
+        {
+            #bindings
+            if !::core::convert::identity::<bool>(#expr) {
+                return Err(::bin_data::stream::#error::AssertionFailed {
+                    message: #message,
+                    expression: #expression,
+                });
+            }
+        }
+    }
+}
+
+/// The asserted expression as written in the source, or its tokens if the source is not available.
+fn expression_text(directive: &Directive, expr: &Expr, message: &LitStr) -> String {
+    let source = directive.paren_token.span.join().source_text();
+    let text = source.zip(message.span().source_text()).and_then(|(source, message)| {
+        let arguments = source.strip_prefix('(')?.strip_suffix(')')?.trim_end();
+        let arguments = arguments.strip_suffix(',').unwrap_or(arguments).trim_end();
+        let expr = arguments.strip_suffix(message.as_str())?.trim_end().strip_suffix(',')?;
+        Some(expr.trim().to_string())
+    });
+    text.unwrap_or_else(|| expr.to_token_stream().to_string())
+}
+
+/// A single entry, or consecutive bit-level fields packed into a shared container.
+enum Segment<'a, 'b> {
+    Entry(&'a Entry, &'b Option<ExtractedArgs<'a>>),
+    Bits(Vec<(&'a Field, &'b ExtractedArgs<'a>, u32)>),
+}
+
+fn segments<'a, 'b>(
+    entries: impl Iterator<Item = (&'a Entry, &'b Option<ExtractedArgs<'a>>)>,
+) -> Vec<Segment<'a, 'b>> {
+    let mut segments = Vec::new();
+    for (entry, args) in entries {
+        let bit_field = match (entry, args) {
+            (Entry::Field(field), Some(args)) => args.bits.map(|bits| (field, args, bits.value.0)),
+            _ => None,
+        };
+        match (bit_field, segments.last_mut()) {
+            // an explicit container always starts a new group
+            (Some(bit_field), Some(Segment::Bits(group)))
+                if bit_field.1.container.is_none() && !BitLayout::is_complete(group) => {
+                group.push(bit_field)
+            }
+            (Some(bit_field), _) => segments.push(Segment::Bits(vec![bit_field])),
+            (None, _) => segments.push(Segment::Entry(entry, args)),
+        }
+    }
+    segments
+}
+
+/// Layout of a bitfield container: its type, and the shift and mask for every field.
+struct BitLayout {
+    container: Ident,
+    container_bits: u32,
+    placements: Vec<(u32, u64)>,
+}
+
+impl BitLayout {
+    /// A group is complete once its fields fill the container given by `container = uN` on the
+    /// first field, or otherwise once they add up to 8, 16, 32, or 64 bits.
+    fn is_complete(group: &[(&Field, &ExtractedArgs, u32)]) -> bool {
+        let total: u32 = group.iter().map(|&(_, _, bits)| bits).sum();
+        match group[0].1.container {
+            Some(container) => total >= container.value.0,
+            None => matches!(total, 8 | 16 | 32 | 64),
+        }
+    }
+
+    fn new(group: &[(&Field, &ExtractedArgs, u32)], bit_order: BitOrder) -> Result<Self, TokenStream> {
+        let total: u32 = group.iter().map(|&(_, _, bits)| bits).sum();
+        let container = match (group[0].1.container, total) {
+            (Some(container), total) if total == container.value.0 => format_ident!("u{}", total),
+            (None, 8 | 16 | 32 | 64) => format_ident!("u{}", total),
+            (container, _) => {
+                let msg = match container {
+                    Some(container) => format!(
+                        "bit-level fields must add up to {} bits for container `{}`, found {total}",
+                        container.value.0, container.token,
+                    ),
+                    None => format!("bit-level fields must add up to 8, 16, 32, or 64 bits, found {total}"),
+                };
+                let names = group.iter().map(|(field, _, _)| &field.name);
+                let types = group.iter().map(|(field, _, _)| &field.r#type);
+                return Err(quote_spanned! { group[0].0.name.span() =>
+                    let (#(#names,)*): (#(#types,)*) = compile_error!(#msg);
+                });
+            }
+        };
+        let mut offset = 0;
+        let placements = group.iter()
+            .map(|&(_, _, bits)| {
+                let shift = match bit_order {
+                    BitOrder::LsbFirst => offset,
+                    BitOrder::MsbFirst => total - offset - bits,
+                };
+                offset += bits;
+                (shift, u64::MAX >> (64 - bits))
+            })
+            .collect();
+        Ok(BitLayout { container, container_bits: total, placements })
+    }
+}
+
+/// Options that cannot be combined with `bits`.
+fn check_bit_field(args: &ExtractedArgs) -> TokenStream {
+    let mut errors = args.errors.clone();
+    let mut reject = |span: Span, option: &str| {
+        let msg = format!("`{option}` cannot be used together with `bits`");
+        errors.extend(quote_spanned!(span => compile_error!(#msg);));
+    };
+    if let Some(endian) = args.endian { reject(endian.span(), "endian"); }
+    if let Some(decode) = args.decode.calculate { reject(decode.span(), "decode"); }
+    if let Some(condition) = args.condition { reject(condition.span(), "if"); }
+    for assign in args.decode.args_assign.iter().chain(&args.encode.args_assign) {
+        reject(assign.name.span(), "args");
+    }
+    errors
+}
+
+fn decode_bits(
+    config: &BodyConfig,
+    group: &[(&Field, &ExtractedArgs, u32)],
+) -> TokenStream {
+    let BitLayout { container, container_bits, placements } = match BitLayout::new(group, config.bit_order) {
+        Ok(layout) => layout,
+        Err(err) => return err,
+    };
+    let bits = Ident::new("bits", Span::mixed_site());
+    let endian = decide_endian(group[0].0.name.span(), None, config.endian);
+    let in_container = config.in_field(group[0].0);
+    let fields = group.iter().zip_eq(placements).map(|(&(field, args, width), (shift, mask))| {
+        let Field { name, r#type, .. } = field;
+        let errors = check_bit_field(args);
+        let field_name = field_name_literal(field);
+        let in_field = config.in_field(field);
+        let mut value = quote!(#bits);
+        if shift != 0 {
+            let shift = Literal::u32_unsuffixed(shift);
+            value = quote!((#value >> #shift));
+        }
+        if width != container_bits {
+            let mask = Literal::u64_unsuffixed(mask);
+            value = quote!((#value & #mask));
+        }
+        quote! {
+            #errors
+            let #name: #r#type = <#r#type as ::core::convert::TryFrom<#container>>::try_from(#value)
+                .map_err(|_| ::bin_data::stream::DecodeError::InvalidData(#field_name))#in_field?;
+        }
+    });
+    quote! {
+        let #bits: #container = <#container>::decode_with(
+            reader, #endian,
+            ArgsBuilderFinished::finish(<#container as Context<dir::Read>>::args_builder()),
+        )#in_container?;
+        #(#fields)*
+    }
+}
+
+fn decode_body(config: &BodyConfig, constructor: TokenStream, body: &Body) -> TokenStream {
+    let field_args = extract_body_args(body);
+    let fields = body.fields().map(Field::binding);
+    let entries = segments(body.entries().zip_eq(&field_args))
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Entry(entry, arg) => decode_entry(config, body, entry, arg),
+            Segment::Bits(group) => decode_bits(config, &group),
+        });
+    quote! {
+        #(#entries)*
+        Ok(#constructor { #(#fields),* })
+    }
+}
+
+pub fn impl_decode(input: &Input, args: &ExtractedArgs, result: &mut TokenStream) {
     let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
-    let fields = input.fields().map(|field| &field.name);
-    let global_endian = args.endian.map_or(EndianConfig::None, |t| t.value);
-    let entries = input.entries.iter().zip_eq(field_args)
-        .map(|(entry, arg)| decode_entry(global_endian, entry, arg));
+    let config = BodyConfig::new(input, args);
+    let global_endian = config.endian;
+    let mut errors = args.errors.clone();
+    let declared_args = DeclaredArgs::new(input, args, &args.decode, "Decode");
+    let body = match &input.data {
+        Data::Struct { body, .. } => decode_body(&config, quote!(Self), body),
+        Data::Enum { variants, .. } => {
+            check_enum_args(input, args, &mut errors);
+            let tag_source = TagSource::new(args, &args.decode);
+            let read_tag = match tag_source {
+                TagSource::Inline(tag_type) => {
+                    let endian = decide_endian(tag_type.span(), None, global_endian);
+                    let args = quote_spanned! { tag_type.span() =>
+                        ArgsBuilderFinished::finish(<#tag_type as Context<dir::Read>>::args_builder())
+                    };
+                    let decode = config.decode(tag_type.span(), tag_type, &endian, args);
+                    quote_spanned!(tag_type.span() => let tag: #tag_type = #decode?;)
+                }
+                // already in scope as a declared argument
+                TagSource::External(_) | TagSource::Missing => TokenStream::new(),
+            };
+            let arms = variants.iter().zip_eq(variant_tags(variants))
+                .filter_map(|(variant, tag_value)| {
+                    let tag_value = tag_value?;
+                    let name = &variant.name;
+                    let body = decode_body(&config.for_variant(variant), quote!(Self::#name), &variant.body);
+                    Some(quote!(if tag == (#tag_value) { #body }))
+                });
+            let unknown = variants.iter().find(|variant| is_unknown(variant))
+                .and_then(|variant| Some((&variant.name, &variant.body.fields().next()?.member)));
+            let fallback = match unknown {
+                Some((name, member)) => quote!(Ok(Self::#name { #member: tag })),
+                None => {
+                    let type_name = type_name_literal(input);
+                    quote!(Err(::bin_data::stream::DecodeError::InvalidData(#type_name)))
+                }
+            };
+            quote! {
+                #read_tag
+                #(#arms else)* {
+                    #fallback
+                }
+            }
+        }
+    };
+    let (args_builder, args_type, args_init, args_binding) = declared_args.usage();
+    let purpose = if declared_args.shared { "decoding and encoding" } else { "decoding" };
+    let definitions = declared_args.definitions(input, purpose);
     let endian_overwrite = global_endian.endian_overwrite();
     let global_endian = global_endian.endian_input();
     let name = &input.name;
+    let (decode_trait, decode_fn, reader, untracked) = match &config.borrowed {
+        None => (
+            quote!(::bin_data::data::Decode<#args_type>),
+            quote!(decode_tracked<R: std::io::Read + ?Sized>),
+            quote!(R),
+            quote! {
+                fn decode_with<R: std::io::Read + ?Sized>(
+                    reader: &mut R, endian: #global_endian, args: #args_type,
+                ) -> Result<Self, ::bin_data::stream::DecodeError> {
+                    Self::decode_tracked(&mut ::bin_data::stream::PositionTracker::new(reader), endian, args)
+                }
+            },
+        ),
+        Some(lifetime) => (
+            quote!(::bin_data::data::DecodeBorrowed<#lifetime, #args_type>),
+            quote!(decode_borrowed_with),
+            quote!(&#lifetime [u8]),
+            TokenStream::new(),
+        ),
+    };
+    // types not borrowing from the input can still be elements of types that do
+    if config.borrowed.is_none() {
+        let mut borrowed_generics = input.generics.clone();
+        borrowed_generics.params.insert(0, syn::parse_quote!('__de));
+        let (borrowed_impl_generics, _, _) = borrowed_generics.split_for_impl();
+        result.extend(quote! {
+            impl #borrowed_impl_generics ::bin_data::data::DecodeBorrowed<'__de, #args_type>
+                for #name #type_generics #where_clause {
+                fn decode_borrowed_with(
+                    reader: &mut ::bin_data::stream::PositionTracker<&'__de [u8]>, endian: #global_endian, args: #args_type,
+                ) -> Result<Self, ::bin_data::stream::DecodeError> {
+                    <Self as ::bin_data::data::Decode<#args_type>>::decode_tracked(reader, endian, args)
+                }
+            }
+        });
+    }
     result.extend(quote! {
+        #errors
+        #definitions
         impl #impl_generics ::bin_data::context::Context<::bin_data::stream::dir::Read>
             for #name #type_generics #where_clause {
             type EndianContext = #global_endian;
-            type ArgsBuilder = ::bin_data::context::NoArgs;
-            fn args_builder() -> Self::ArgsBuilder { ::bin_data::context::NoArgs }
+            type ArgsBuilder = #args_builder;
+            fn args_builder() -> Self::ArgsBuilder { #args_init }
         }
-        impl #impl_generics ::bin_data::data::Decode for #name #type_generics #where_clause {
-            #[allow(unused_import)]
-            fn decode_with<R: std::io::Read + ?Sized>(reader: &mut R, endian: #global_endian, args: ())
-                -> Result<Self, ::bin_data::stream::DecodeError> {
+        impl #impl_generics #decode_trait for #name #type_generics #where_clause {
+            #untracked
+            #[allow(unused_import, clippy::unit_arg)]
+            fn #decode_fn(
+                reader: &mut ::bin_data::stream::PositionTracker<#reader>, endian: #global_endian, args: #args_type,
+            ) -> Result<Self, ::bin_data::stream::DecodeError> {
+                #[allow(unused_variables)]
+                let struct_start = reader.position();
                 #endian_overwrite
-                use ::bin_data::stream::{Stream, dir};
+                #args_binding
+                use ::bin_data::stream::{Stream, StreamPosition, dir};
                 use ::bin_data::context::{Context, ArgsBuilderFinished};
-                #(#entries)*
-                Ok(Self { #(#fields),* })
+                #body
             }
         }
     });
 }
 
+/// What the generated encoding code does with every entry.
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum EncodeTarget {
+    /// Write the entries to `writer`, in `encode_tracked`.
+    Writer,
+    /// Add up the sizes of the entries, in `encoded_size_at`.
+    Size,
+}
+
+impl EncodeTarget {
+    fn size_ident() -> Ident { Ident::new("size", Span::mixed_site()) }
+    fn position_ident() -> Ident { Ident::new("position", Span::mixed_site()) }
+
+    fn encode(self, span: Span, value: TokenStream, endian: TokenStream, args: TokenStream, map_err: TokenStream) -> TokenStream {
+        match self {
+            EncodeTarget::Writer => quote_spanned!(span => #value.encode_tracked(writer, #endian, #args)#map_err?),
+            EncodeTarget::Size => {
+                let size = Self::size_ident();
+                let position = Self::position_ident();
+                quote_spanned!(span => #size += #value.encoded_size_at(#position + #size, #endian, #args)#map_err?)
+            }
+        }
+    }
+
+    /// Whether `encoded_size_at` can add up the sizes in a body, i.e., it contains no directives
+    /// depending on the position, such as `@align`. Otherwise, the body is encoded to a counter.
+    fn supports_size(body: &Body) -> bool {
+        body.entries().all(|entry| match entry {
+            Entry::Directive(directive) => ["magic", "pad", "assert"].iter().any(|d| directive.directive == d),
+            Entry::Field(_) => true,
+        })
+    }
+}
+
 fn encode_entry(
-    global_endian: EndianConfig,
+    config: &BodyConfig,
+    target: EncodeTarget,
+    body: &Body,
     entry: &Entry,
     args: &Option<ExtractedArgs>,
 ) -> TokenStream {
     match entry {
+        Entry::Directive(directive) if directive.directive == "assert" => assertion(body, entry, directive, false),
+        Entry::Directive(directive) if target == EncodeTarget::Size => {
+            let size = EncodeTarget::size_ident();
+            let arguments = &directive.arguments;
+            match directive.directive.to_string().as_str() {
+                "magic" => quote!(#size += ::bin_data::stream::magic_size(&(#arguments)) as u64;),
+                _ => quote!(#size += (#arguments) as u64;),
+            }
+        }
         Entry::Directive(directive) => quote!(writer.#directive?;),
-        Entry::Field(Field { name, r#type, .. }) => {
+        Entry::Field(field @ Field { name, r#type, .. }) => {
+            let in_field = config.in_field(field);
             let args = args.as_ref().unwrap();
             let arg_setters = args.encode.arg_setters();
-            let endian = decide_endian(name.span(), args.endian, global_endian);
+            let endian = decide_endian(name.span(), args.endian, config.endian);
+            if let Some(condition) = args.condition {
+                let inner = match option_inner(r#type) {
+                    Ok(inner) => inner,
+                    Err(err) => return quote!(#err;),
+                };
+                let value = Ident::new("value", Span::mixed_site());
+                let present = Ident::new("present", Span::mixed_site());
+                let field_name = field_name_literal(field);
+                let bindings = bind_mentioned(body, entry, condition, false);
+                let encode = target.encode(
+                    name.span(), quote!(#value), endian,
+                    quote_spanned!(name.span() => ArgsBuilderFinished::finish(<#inner as Context<dir::Write>>::args_builder() #arg_setters)),
+                    in_field.clone(),
+                );
+                return quote_spanned! { name.span() =>
+                    let #present = { #bindings ::core::convert::identity::<bool>(#condition) };
+                    match (#present, &#name) {
+                        (true, ::core::option::Option::Some(#value)) => #encode,
+                        (false, ::core::option::Option::None) => {}
+                        _ => return Err(::bin_data::stream::EncodeError::InvalidData(#field_name))#in_field,
+                    }
+                };
+            }
             let builder = if args.encode.calculate.is_none() {
                 quote_spanned!(name.span() => <#r#type as Context<dir::Write>>::args_builder())
             } else {
                 quote_spanned!(name.span() => Context::<dir::Write>::args_builder_of_val(&#name))
             };
-            quote_spanned! { name.span() =>
-                #name.encode_with(writer, #endian, ArgsBuilderFinished::finish(#builder #arg_setters))?;
-            }
+            let args = quote_spanned!(name.span() => ArgsBuilderFinished::finish(#builder #arg_setters));
+            let encode = target.encode(name.span(), quote!(#name), endian, args, in_field);
+            quote!(#encode;)
         }
     }
 }
 
-pub fn impl_encode(
-    input: &Input,
-    args: &ExtractedArgs,
-    field_args: &[Option<ExtractedArgs>],
-    result: &mut TokenStream,
-) {
-    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
-    let fields = input.fields().map(|field| &field.name);
-    let entries = input.entries.iter().zip_eq(field_args);
+fn encode_bits(
+    config: &BodyConfig,
+    target: EncodeTarget,
+    group: &[(&Field, &ExtractedArgs, u32)],
+) -> TokenStream {
+    let BitLayout { container, container_bits, placements } = match BitLayout::new(group, config.bit_order) {
+        Ok(layout) => layout,
+        Err(err) => return err,
+    };
+    let bits = Ident::new("bits", Span::mixed_site());
+    let endian = decide_endian(group[0].0.name.span(), None, config.endian);
+    let in_container = config.in_field(group[0].0);
+    let fields = group.iter().zip_eq(placements).map(|(&(field, _, width), (shift, mask))| {
+        let Field { name, r#type, .. } = field;
+        let field_name = field_name_literal(field);
+        let in_field = config.in_field(field);
+        let mut value = quote! {
+            <#container as ::core::convert::TryFrom<#r#type>>::try_from(
+                *::core::borrow::Borrow::<#r#type>::borrow(&#name)
+            ).ok()
+        };
+        if width != container_bits {
+            let mask = Literal::u64_unsuffixed(mask);
+            value = quote!(#value.filter(|value| *value <= #mask));
+        }
+        value = quote!(#value.ok_or(::bin_data::stream::EncodeError::InvalidData(#field_name))#in_field?);
+        if shift != 0 {
+            let shift = Literal::u32_unsuffixed(shift);
+            value = quote!((#value << #shift));
+        }
+        quote!(#bits |= #value;)
+    });
+    let encode = target.encode(
+        Span::call_site(), quote!(#bits), endian,
+        quote!(ArgsBuilderFinished::finish(<#container as Context<dir::Write>>::args_builder())),
+        in_container,
+    );
+    quote! {
+        let mut #bits: #container = 0;
+        #(#fields)*
+        #encode;
+    }
+}
+
+fn encode_body(config: &BodyConfig, target: EncodeTarget, body: &Body) -> TokenStream {
+    let field_args = extract_body_args(body);
+    let entries = body.entries().zip_eq(&field_args);
     let temps = entries.clone()
         .filter_map(|(entry, arg)| {
             let field = entry.as_temp()?;
@@ -201,35 +1056,264 @@ pub fn impl_encode(
                 let #name: #r#type = compile_error!("temporary field requires an `encode` attribute");
             },
         });
-    let global_endian = args.endian.map_or(EndianConfig::None, |t| t.value);
-    let entries = entries.clone()
+    let entries = entries
         .filter(|&(_, arg)| match arg.as_ref() {
             None => true,
             Some(arg) => arg.decode.calculate.is_none(),
-        })
-        .map(|(entry, arg)| encode_entry(global_endian, entry, arg));
+        });
+    let entries = segments(entries)
+        .into_iter()
+        .map(|segment| match segment {
+            Segment::Entry(entry, arg) => encode_entry(config, target, body, entry, arg),
+            Segment::Bits(group) => encode_bits(config, target, &group),
+        });
+    quote! {
+        #(#temps)*
+        #(#entries)*
+    }
+}
+
+pub fn impl_encode(input: &Input, args: &ExtractedArgs, result: &mut TokenStream) {
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    let config = BodyConfig::new(input, args);
+    let global_endian = config.endian;
+    let name = &input.name;
+    let declared_args = DeclaredArgs::new(input, args, &args.encode, "Encode");
+    let mut encoded_size = TokenStream::new();
+    let body = match &input.data {
+        Data::Struct { body, .. } => {
+            let fields = body.fields().map(Field::binding).collect_vec();
+            if EncodeTarget::supports_size(body) {
+                let size = EncodeTarget::size_ident();
+                let size_body = encode_body(&config, EncodeTarget::Size, body);
+                encoded_size = quote! {
+                    #[allow(unused_variables)]
+                    let Self { #(#fields),* } = self;
+                    #[allow(unused_mut)]
+                    let mut #size: u64 = 0;
+                    #size_body
+                    Ok(#size)
+                };
+            }
+            let body = encode_body(&config, EncodeTarget::Writer, body);
+            quote! {
+                #[allow(unused_variables)]
+                let Self { #(#fields),* } = self;
+                #body
+                Ok(())
+            }
+        }
+        Data::Enum { variants, .. } => {
+            let tag_source = TagSource::new(args, &args.encode);
+            let type_name = type_name_literal(input);
+            let write_tag = match tag_source {
+                TagSource::Inline(tag_type) => {
+                    let endian = decide_endian(tag_type.span(), None, global_endian);
+                    quote_spanned! { tag_type.span() =>
+                        let tag: #tag_type = ::bin_data::data::Tagged::tag(self);
+                        tag.encode_with(
+                            writer, #endian,
+                            ArgsBuilderFinished::finish(<#tag_type as Context<dir::Write>>::args_builder()),
+                        )?;
+                    }
+                }
+                TagSource::External(_) => quote! {
+                    if tag != ::bin_data::data::Tagged::tag(self) {
+                        let err = "tag does not match the variant";
+                        return Err(::bin_data::stream::EncodeError::InvalidArgument(#type_name, err));
+                    }
+                },
+                TagSource::Missing => TokenStream::new(),
+            };
+            // a raw tag of a known variant would decode as that variant instead
+            let known_tags = variant_tags(variants).into_iter().flatten().collect_vec();
+            let check_unknown = variants.iter().find(|variant| is_unknown(variant))
+                .and_then(|variant| Some((&variant.name, &variant.body.fields().next()?.member)))
+                .filter(|_| !known_tags.is_empty())
+                .map(|(name, member)| quote! {
+                    if let Self::#name { #member: tag, .. } = self {
+                        if #(*tag == (#known_tags))||* {
+                            let err = "raw tag of a known variant";
+                            return Err(::bin_data::stream::EncodeError::InvalidArgument(#type_name, err));
+                        }
+                    }
+                });
+            let arms = variants.iter().map(|variant| {
+                let name = &variant.name;
+                // the raw tag is already written, and it is the only field
+                if is_unknown(variant) { return quote!(Self::#name { .. } => {}); }
+                let fields = variant.body.fields().map(Field::binding);
+                let body = encode_body(&config.for_variant(variant), EncodeTarget::Writer, &variant.body);
+                quote! {
+                    #[allow(unused_variables)]
+                    Self::#name { #(#fields,)* .. } => { #body }
+                }
+            });
+            impl_tagged(input, args, variants, result);
+            quote! {
+                #check_unknown
+                #write_tag
+                match self { #(#arms)* }
+                Ok(())
+            }
+        }
+    };
+    let (args_builder, args_type, args_init, args_binding) = declared_args.usage();
+    let definitions = if declared_args.shared {
+        TokenStream::new()
+    } else {
+        declared_args.definitions(input, "encoding")
+    };
     let endian_overwrite = global_endian.endian_overwrite();
     let global_endian = global_endian.endian_input();
-    let name = &input.name;
+    let position = EncodeTarget::position_ident();
+    encoded_size = if encoded_size.is_empty() {
+        quote! {
+            fn encoded_size_at(
+                &self, #position: u64, endian: #global_endian, args: #args_type,
+            ) -> Result<u64, ::bin_data::stream::EncodeError> {
+                let mut counter = ::bin_data::stream::PositionTracker::counter_at(#position);
+                self.encode_tracked(&mut counter, endian, args)?;
+                Ok(counter.position() - #position)
+            }
+        }
+    } else {
+        quote! {
+            fn encoded_size_with(
+                &self, endian: #global_endian, args: #args_type,
+            ) -> Result<u64, ::bin_data::stream::EncodeError> {
+                self.encoded_size_at(0, endian, args)
+            }
+            #[allow(unused_import, clippy::unit_arg)]
+            fn encoded_size_at(
+                &self, #position: u64, endian: #global_endian, args: #args_type,
+            ) -> Result<u64, ::bin_data::stream::EncodeError> {
+                #endian_overwrite
+                #args_binding
+                use ::bin_data::stream::dir;
+                use ::bin_data::context::{Context, ArgsBuilderFinished};
+                #encoded_size
+            }
+        }
+    };
     result.extend(quote! {
+        #definitions
         impl #impl_generics ::bin_data::context::Context<::bin_data::stream::dir::Write>
             for #name #type_generics #where_clause {
             type EndianContext = #global_endian;
-            type ArgsBuilder = ::bin_data::context::NoArgs;
-            fn args_builder() -> Self::ArgsBuilder { ::bin_data::context::NoArgs }
+            type ArgsBuilder = #args_builder;
+            fn args_builder() -> Self::ArgsBuilder { #args_init }
         }
-        impl #impl_generics ::bin_data::data::Encode for #name #type_generics #where_clause {
-            #[allow(unused_import)]
-            fn encode_with<W: std::io::Write + ?Sized>(&self, writer: &mut W, endian: #global_endian, args: ())
-                -> Result<(), ::bin_data::stream::EncodeError> {
+        impl #impl_generics ::bin_data::data::Encode<#args_type> for #name #type_generics #where_clause {
+            fn encode_with<W: std::io::Write + ?Sized>(
+                &self, writer: &mut W, endian: #global_endian, args: #args_type,
+            ) -> Result<(), ::bin_data::stream::EncodeError> {
+                self.encode_tracked(&mut ::bin_data::stream::PositionTracker::new(writer), endian, args)
+            }
+            #[allow(unused_import, clippy::unit_arg)]
+            fn encode_tracked<W: std::io::Write + ?Sized>(
+                &self, writer: &mut ::bin_data::stream::PositionTracker<W>, endian: #global_endian, args: #args_type,
+            ) -> Result<(), ::bin_data::stream::EncodeError> {
+                #[allow(unused_variables)]
+                let struct_start = writer.position();
                 #endian_overwrite
-                use ::bin_data::stream::{Stream, dir};
+                #args_binding
+                use ::bin_data::stream::{Stream, StreamPosition, dir};
                 use ::bin_data::context::{Context, ArgsBuilderFinished};
-                #[allow(unused_variables)]
-                let Self { #(#fields),* } = self;
-                #(#temps)*
-                #(#entries)*
-                Ok(())
+                #body
+            }
+            #encoded_size
+        }
+    });
+}
+
+/// Implement `FixedSize` for a `struct`, if its layout does not depend on the values or positions.
+///
+/// Whether the field types are fixed-size is unknown here, so they are required in the `where`
+/// clause. The bounds are made higher-ranked so that unsatisfied ones simply disable the impl.
+/// Directives mentioning fields, temporaries, or declared arguments are not constant, and neither
+/// is the layout then.
+pub fn impl_fixed_size(input: &Input, args: &ExtractedArgs, result: &mut TokenStream) {
+    let Data::Struct { body, .. } = &input.data else { return };
+    let locals = body.entries()
+        .filter_map(|entry| match entry {
+            Entry::Field(field) => Some(&field.name),
+            Entry::Directive(_) => None,
+        })
+        .chain(args.decode.args_decl.iter().chain(&args.encode.args_decl).map(|arg| &arg.name))
+        .collect::<HashSet<_>>();
+    let field_args = extract_body_args(body);
+    let mut sizes = Vec::new();
+    let mut bounds = Vec::new();
+    for segment in segments(body.entries().zip_eq(&field_args)) {
+        match segment {
+            Segment::Entry(Entry::Directive(directive), _) => {
+                let arguments = &directive.arguments;
+                let size = match directive.directive.to_string().as_str() {
+                    "magic" => quote!(::bin_data::stream::magic_size(&(#arguments))),
+                    "pad" => quote!((#arguments)),
+                    "assert" => continue,
+                    _ => return,
+                };
+                let mut mentioned = HashSet::new();
+                collect_idents(arguments.clone(), &mut mentioned);
+                if mentioned.iter().any(|ident| locals.contains(ident)) { return; }
+                sizes.push(size);
+            }
+            Segment::Entry(Entry::Field(field), args) => {
+                let args = args.as_ref().unwrap();
+                if args.condition.is_some() { return; }
+                if args.decode.calculate.is_some() { continue; }
+                let r#type = &field.r#type;
+                sizes.push(quote!(<#r#type as ::bin_data::data::FixedSize>::ENCODED_SIZE));
+                bounds.push(quote!(for<'__fixed> #r#type: ::bin_data::data::FixedSize));
+            }
+            Segment::Bits(group) => {
+                let total: u32 = group.iter().map(|&(_, _, bits)| bits).sum();
+                sizes.push(Literal::usize_unsuffixed(total as usize / 8).into_token_stream());
+            }
+        }
+    }
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    let predicates = where_clause.map(|clause| &clause.predicates).into_iter().flatten();
+    let name = &input.name;
+    result.extend(quote! {
+        impl #impl_generics ::bin_data::data::FixedSize for #name #type_generics
+            where #(#predicates,)* #(#bounds,)* {
+            const ENCODED_SIZE: usize = 0 #(+ #sizes)*;
+        }
+    });
+}
+
+fn impl_tagged(
+    input: &Input,
+    args: &ExtractedArgs,
+    variants: &Punctuated<Variant, Token![,]>,
+    result: &mut TokenStream,
+) {
+    let tag_type = match (TagSource::new(args, &args.decode), TagSource::new(args, &args.encode)) {
+        (TagSource::Inline(tag_type) | TagSource::External(tag_type), _) => tag_type,
+        (_, TagSource::Inline(tag_type) | TagSource::External(tag_type)) => tag_type,
+        _ => return,
+    };
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    let name = &input.name;
+    let arms = variants.iter().zip_eq(variant_tags(variants))
+        .map(|(variant, tag_value)| {
+            let name = &variant.name;
+            match tag_value {
+                Some(tag_value) => quote!(Self::#name { .. } => #tag_value,),
+                None => match variant.body.fields().next() {
+                    Some(Field { member, .. }) => quote!(Self::#name { #member: tag, .. } => ::core::clone::Clone::clone(tag),),
+                    None => quote!(Self::#name { .. } => unreachable!(),),
+                },
+            }
+        });
+    result.extend(quote! {
+        impl #impl_generics ::bin_data::data::Tagged for #name #type_generics #where_clause {
+            type Tag = #tag_type;
+            fn tag(&self) -> #tag_type {
+                match self { #(#arms)* }
             }
         }
     });
@@ -1,49 +1,180 @@
-use itertools::Itertools;
-use proc_macro2::{Ident, TokenStream};
-use quote::{quote, ToTokens};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote, ToTokens};
 use syn::punctuated::Punctuated;
-use syn::{Token, parenthesized, braced, Attribute, Visibility, Type, Generics, Meta, Expr, Error, LitStr};
+use syn::{Token, parenthesized, braced, Attribute, Visibility, Type, Generics, Meta, MetaList, Expr, Error, LitInt, LitStr, DeriveInput, Member, Index};
+use syn::ext::IdentExt;
 use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
 use syn::token::{Brace, Paren};
 
-/// Input for the macro. Looks like a `struct` definition.
+/// Input for the macro. Looks like a `struct` or `enum` definition.
 pub struct Input {
     pub known_attrs: Vec<KnownAttribute>,
     pub attrs: Vec<Attribute>,
     pub vis: Visibility,
-    pub struct_token: Token![struct],
     pub name: Ident,
     pub generics: Generics,
-    pub brace_token: Brace,
-    pub entries: Punctuated<Entry, Token![,]>,
+    pub data: Data,
 }
 
-impl Input {
-    pub fn fields(&self) -> impl Iterator<Item = &Field> {
-        self.entries.iter().filter_map(Entry::as_field)
+impl Parse for Input {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let (known_attrs, attrs) = parse_attributes(input, AttributeTarget::Type)?;
+        let vis = input.parse()?;
+        let lookahead = input.lookahead1();
+        if !lookahead.peek(Token![struct]) && !lookahead.peek(Token![enum]) {
+            return Err(lookahead.error());
+        }
+        let struct_token: Option<Token![struct]> = input.parse()?;
+        let enum_token: Option<Token![enum]> = input.parse()?;
+        let name = input.parse()?;
+        let generics = input.parse()?;
+        let data = match (struct_token, enum_token) {
+            (Some(struct_token), _) => {
+                let body = input.parse()?;
+                let semi_token = match body {
+                    Body::Named { .. } => None,
+                    _ => Some(input.parse()?),
+                };
+                Data::Struct { struct_token, body, semi_token }
+            }
+            (None, Some(enum_token)) => {
+                let contents;
+                Data::Enum {
+                    enum_token,
+                    brace_token: braced!(contents in input),
+                    variants: Punctuated::parse_terminated(&contents)?,
+                }
+            }
+            (None, None) => unreachable!(),
+        };
+        Ok(Input { known_attrs, attrs, vis, name, generics, data })
     }
 }
 
-impl Parse for Input {
+pub enum Data {
+    /// Structs: `struct Name { entries... }`, `struct Name(entries...);`, or `struct Name;`
+    Struct {
+        struct_token: Token![struct],
+        body: Body,
+        semi_token: Option<Token![;]>,
+    },
+    /// Tagged unions: `enum Name { Variant { entries... }, ... }`
+    Enum {
+        enum_token: Token![enum],
+        brace_token: Brace,
+        variants: Punctuated<Variant, Token![,]>,
+    },
+}
+
+/// Body of a `struct` or an `enum` variant.
+pub enum Body {
+    /// Named fields: `{ entries... }`
+    Named {
+        brace_token: Brace,
+        entries: Punctuated<Entry, Token![,]>,
+    },
+    /// Positional fields: `(entries...)`
+    Unnamed {
+        paren_token: Paren,
+        entries: Punctuated<Entry, Token![,]>,
+    },
+    /// No body at all: unit structs and unit variants.
+    Unit,
+}
+
+impl Body {
+    pub fn entries(&self) -> impl Iterator<Item = &Entry> + Clone {
+        let entries = match self {
+            Body::Named { entries, .. } | Body::Unnamed { entries, .. } => Some(entries),
+            Body::Unit => None,
+        };
+        entries.into_iter().flatten()
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = &Field> + Clone {
+        self.entries().filter_map(Entry::as_field)
+    }
+}
+
+impl Parse for Body {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let attrs = Attribute::parse_outer(input)?;
-        let (ResultVec(known_attrs), attrs) = attrs.into_iter()
-            .map(|attr| KnownAttribute::new(attr, false))
-            .partition_result();
         let contents;
-        Ok(Input {
-            known_attrs: known_attrs?,
-            attrs,
-            vis: input.parse()?,
-            struct_token: input.parse()?,
-            name: input.parse()?,
-            generics: input.parse()?,
-            brace_token: braced!(contents in input),
-            entries: Punctuated::parse_terminated(&contents)?,
-        })
+        if input.peek(Brace) {
+            Ok(Body::Named {
+                brace_token: braced!(contents in input),
+                entries: Punctuated::parse_terminated(&contents)?,
+            })
+        } else if input.peek(Paren) {
+            let paren_token = parenthesized!(contents in input);
+            let mut entries = Punctuated::new();
+            let mut index = 0;
+            while !contents.is_empty() {
+                entries.push_value(if contents.peek(Token![@]) {
+                    Entry::Directive(contents.parse()?)
+                } else {
+                    Entry::Field(Field::parse_positional(&contents, &mut index)?)
+                });
+                if contents.is_empty() { break; }
+                entries.push_punct(contents.parse()?);
+            }
+            Ok(Body::Unnamed { paren_token, entries })
+        } else {
+            Ok(Body::Unit)
+        }
+    }
+}
+
+impl ToTokens for Body {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let fields = self.fields();
+        match self {
+            Body::Named { brace_token, .. } => brace_token.surround(tokens, |tokens| {
+                tokens.extend(quote! { #(#fields),* })
+            }),
+            Body::Unnamed { paren_token, .. } => paren_token.surround(tokens, |tokens| {
+                tokens.extend(quote! { #(#fields),* })
+            }),
+            Body::Unit => {}
+        }
+    }
+}
+
+pub struct Variant {
+    pub known_attrs: Vec<KnownAttribute>,
+    pub attrs: Vec<Attribute>,
+    pub name: Ident,
+    pub body: Body,
+    pub discriminant: Option<(Token![=], Expr)>,
+}
+
+impl Parse for Variant {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let (known_attrs, attrs) = parse_attributes(input, AttributeTarget::Variant)?;
+        let name = input.parse()?;
+        let body = input.parse()?;
+        let discriminant = if input.peek(Token![=]) {
+            Some((input.parse()?, input.parse()?))
+        } else {
+            None
+        };
+        Ok(Variant { known_attrs, attrs, name, body, discriminant })
+    }
+}
+
+impl ToTokens for Variant {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.attrs.iter().for_each(|attr| attr.to_tokens(tokens));
+        self.name.to_tokens(tokens);
+        self.body.to_tokens(tokens);
+        if let Some((eq_token, value)) = &self.discriminant {
+            eq_token.to_tokens(tokens);
+            value.to_tokens(tokens);
+        }
     }
 }
 
+#[allow(clippy::large_enum_variant)]
 pub enum Entry {
     /// Stream directives: `@directive(arguments...)`
     Directive(Directive),
@@ -77,6 +208,7 @@ impl Parse for Entry {
 }
 
 pub struct Directive {
+    #[allow(dead_code)]
     pub at_token: Token![@],
     pub directive: Ident,
     pub paren_token: Paren,
@@ -108,41 +240,72 @@ pub struct Field {
     pub known_attrs: Vec<KnownAttribute>,
     pub attrs: Vec<Attribute>,
     pub kind: FieldKind,
+    pub member: Member,
+    /// Name of the field, or `_0`, `_1`, etc. for positional fields.
     pub name: Ident,
-    pub colon_token: Token![:],
+    /// Absent for positional fields.
+    pub colon_token: Option<Token![:]>,
     pub r#type: Type,
 }
 
-struct ResultVec<T, E>(Result<Vec<T>, E>);
+impl Field {
+    fn parse_positional(input: ParseStream, index: &mut u32) -> syn::Result<Self> {
+        let (known_attrs, attrs) = parse_attributes(input, AttributeTarget::Field)?;
+        let kind = input.parse()?;
+        if let FieldKind::Temp(_) = kind {
+            let name: Ident = input.parse()?;
+            return Ok(Field {
+                known_attrs,
+                attrs,
+                kind,
+                member: Member::Named(name.clone()),
+                name,
+                colon_token: Some(input.parse()?),
+                r#type: input.parse()?,
+            });
+        }
+        let r#type: Type = input.parse()?;
+        let member = Member::Unnamed(Index { index: *index, span: r#type.span() });
+        let name = format_ident!("_{}", *index, span = r#type.span());
+        *index += 1;
+        Ok(Field { known_attrs, attrs, kind, member, name, colon_token: None, r#type })
+    }
 
-impl<T, E> Default for ResultVec<T, E> {
-    fn default() -> Self { ResultVec(Ok(Vec::new())) }
+    /// Bind the field to its name in patterns and `struct` expressions: `name` or `0: _0`.
+    pub fn binding(&self) -> TokenStream {
+        let name = &self.name;
+        match &self.member {
+            Member::Named(_) => quote!(#name),
+            Member::Unnamed(index) => quote!(#index: #name),
+        }
+    }
 }
 
-impl<T, E> Extend<Result<T, E>> for ResultVec<T, E> {
-    fn extend<I: IntoIterator<Item = Result<T, E>>>(&mut self, iter: I) {
-        let Ok(target) = &mut self.0 else { return; };
-        for item in iter {
-            match item {
-                Ok(value) => target.push(value),
-                Err(err) => return self.0 = Err(err),
-            }
+/// Split out the `#[bin_data(...)]` attributes and parse them.
+fn parse_attributes(input: ParseStream, target: AttributeTarget) -> syn::Result<(Vec<KnownAttribute>, Vec<Attribute>)> {
+    let mut known_attrs = Vec::new();
+    let mut attrs = Vec::new();
+    for attr in Attribute::parse_outer(input)? {
+        match KnownAttribute::new(&attr, target) {
+            Some(known_attr) => known_attrs.push(known_attr?),
+            None => attrs.push(attr),
         }
     }
+    Ok((known_attrs, attrs))
 }
 
 impl Parse for Field {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let attrs = Attribute::parse_outer(input)?;
-        let (ResultVec(known_attrs), attrs) = attrs.into_iter()
-            .map(|attr| KnownAttribute::new(attr, true))
-            .partition_result();
+        let (known_attrs, attrs) = parse_attributes(input, AttributeTarget::Field)?;
+        let kind = input.parse()?;
+        let name: Ident = input.parse()?;
         Ok(Field {
-            known_attrs: known_attrs?,
+            known_attrs,
             attrs,
-            kind: input.parse()?,
-            name: input.parse()?,
-            colon_token: input.parse()?,
+            kind,
+            member: Member::Named(name.clone()),
+            name,
+            colon_token: Some(input.parse()?),
             r#type: input.parse()?,
         })
     }
@@ -152,59 +315,103 @@ impl ToTokens for Field {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         self.attrs.iter().for_each(|attr| attr.to_tokens(tokens));
         self.kind.to_tokens(tokens);
-        self.name.to_tokens(tokens);
-        self.colon_token.to_tokens(tokens);
+        if let Some(colon_token) = &self.colon_token {
+            self.name.to_tokens(tokens);
+            colon_token.to_tokens(tokens);
+        }
         self.r#type.to_tokens(tokens);
     }
 }
 
+/// What a `#[bin_data(...)]` attribute is attached to.
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum AttributeTarget {
+    Type,
+    Variant,
+    Field,
+}
+
 pub enum KnownAttribute {
     Endian(WithToken<LitStr, EndianConfig>),
     Encode(Expr),
     Decode(Expr),
+    TagDecl(Type),
+    TagAssign(Expr),
+    If(Expr),
+    Bits(WithToken<LitInt, BitWidth>),
+    Container(WithToken<Ident, BitContainer>),
+    BitOrder(WithToken<LitStr, BitOrder>),
+    Unknown(Ident),
     ArgsDecl {
         direction: Direction,
+        #[allow(dead_code)]
         brace_token: Brace,
         fields: Punctuated<ArgFieldDecl, Token![,]>,
     },
     ArgsAssign {
         direction: Direction,
+        #[allow(dead_code)]
         brace_token: Brace,
         fields: Punctuated<ArgFieldAssign, Token![,]>,
     },
 }
 
 impl KnownAttribute {
-    fn new(attr: Attribute, field: bool) -> Result<syn::Result<KnownAttribute>, Attribute> {
-        if !attr.path().is_ident("bin_data") { return Err(attr); }
-        let Meta::List(list) = attr.meta else { return Err(attr); };
-        Ok(list.parse_args_with(|input: ParseStream| {
-            let cmd: Ident = input.parse()?;
-            fn eq_expr<E: Parse, T>(input: ParseStream, f: impl FnOnce(E) -> T) -> Result<T, Error> {
-                let _: Token![=] = input.parse()?;
-                input.parse().map(f)
+    fn new(attr: &Attribute, target: AttributeTarget) -> Option<syn::Result<KnownAttribute>> {
+        let list = bin_data_meta_list(attr)?;
+        Some(list.parse_args_with(|input: ParseStream| KnownAttribute::parse_with(input, target)))
+    }
+
+    fn parse_with(input: ParseStream, target: AttributeTarget) -> syn::Result<KnownAttribute> {
+        let cmd = Ident::parse_any(input)?;
+        fn eq_expr<E: Parse, T>(input: ParseStream, f: impl FnOnce(E) -> T) -> Result<T, Error> {
+            let _: Token![=] = input.parse()?;
+            input.parse().map(f)
+        }
+        let contents;
+        match cmd.to_string().as_str() {
+            "endian" | "encode" | "decode" | "args" if target == AttributeTarget::Variant => {
+                Err(Error::new(cmd.span(), format!("`{}` is not allowed on `enum` variants", cmd)))
             }
-            let contents;
-            match cmd.to_string().as_str() {
-                "endian" => eq_expr(input, KnownAttribute::Endian),
-                "encode" => eq_expr(input, KnownAttribute::Encode),
-                "decode" => eq_expr(input, KnownAttribute::Decode),
-                "args" if field => Ok(KnownAttribute::ArgsAssign {
-                    direction: input.parse()?,
-                    brace_token: braced!(contents in input),
-                    fields: Punctuated::parse_terminated(&contents)?,
-                }),
-                "args" => Ok(KnownAttribute::ArgsDecl {
-                    direction: input.parse()?,
-                    brace_token: braced!(contents in input),
-                    fields: Punctuated::parse_terminated(&contents)?,
-                }),
-                _ => Err(Error::new(cmd.span(), "unknown attribute for `bin_data`")),
+            "endian" => eq_expr(input, KnownAttribute::Endian),
+            "encode" => eq_expr(input, KnownAttribute::Encode),
+            "decode" => eq_expr(input, KnownAttribute::Decode),
+            "tag" if input.peek(Token![:]) => {
+                let _: Token![:] = input.parse()?;
+                input.parse().map(KnownAttribute::TagDecl)
             }
-        }))
+            "tag" => eq_expr(input, KnownAttribute::TagAssign),
+            "if" if target == AttributeTarget::Field => eq_expr(input, KnownAttribute::If),
+            "if" => Err(Error::new(cmd.span(), "`if` is only allowed on fields")),
+            "bits" if target == AttributeTarget::Field => eq_expr(input, KnownAttribute::Bits),
+            "bits" => Err(Error::new(cmd.span(), "`bits` is only allowed on fields")),
+            "container" if target == AttributeTarget::Field => eq_expr(input, KnownAttribute::Container),
+            "container" => Err(Error::new(cmd.span(), "`container` is only allowed on fields")),
+            "bit_order" if target == AttributeTarget::Type => eq_expr(input, KnownAttribute::BitOrder),
+            "bit_order" => Err(Error::new(cmd.span(), "`bit_order` is only allowed on the type")),
+            "unknown" if target == AttributeTarget::Variant => Ok(KnownAttribute::Unknown(cmd)),
+            "unknown" => Err(Error::new(cmd.span(), "`unknown` is only allowed on `enum` variants")),
+            "args" if target == AttributeTarget::Field => Ok(KnownAttribute::ArgsAssign {
+                direction: input.parse()?,
+                brace_token: braced!(contents in input),
+                fields: Punctuated::parse_terminated(&contents)?,
+            }),
+            "args" => Ok(KnownAttribute::ArgsDecl {
+                direction: input.parse()?,
+                brace_token: braced!(contents in input),
+                fields: Punctuated::parse_terminated(&contents)?,
+            }),
+            _ => Err(Error::new(cmd.span(), "unknown attribute for `bin_data`")),
+        }
     }
 }
 
+fn bin_data_meta_list(attr: &Attribute) -> Option<&MetaList> {
+    if !attr.path().is_ident("bin_data") { return None; }
+    let Meta::List(list) = &attr.meta else { return None; };
+    Some(list)
+}
+
 #[derive(Copy, Clone)]
 pub struct WithToken<T, V> {
     pub token: T,
@@ -262,6 +469,60 @@ impl TryFrom<&'_ LitStr> for EndianConfig {
     }
 }
 
+/// Width of a bit-level field, between 1 and 64 bits.
+#[derive(Copy, Clone)]
+pub struct BitWidth(pub u32);
+
+impl TryFrom<&'_ LitInt> for BitWidth {
+    type Error = Error;
+    fn try_from(bits: &LitInt) -> syn::Result<Self> {
+        match bits.base10_parse()? {
+            bits @ 1..=64 => Ok(BitWidth(bits)),
+            _ => Err(Error::new(bits.span(), "bit width must be between 1 and 64")),
+        }
+    }
+}
+
+/// Container integer for bit-level fields, explicitly chosen using `container = uN`.
+#[derive(Copy, Clone)]
+pub struct BitContainer(pub u32);
+
+impl TryFrom<&'_ Ident> for BitContainer {
+    type Error = Error;
+    fn try_from(container: &Ident) -> syn::Result<Self> {
+        const MSG: &str = "invalid bit container, must be one of `u8`, `u16`, `u32`, `u64`";
+        Ok(match container.to_string().as_str() {
+            "u8" => BitContainer(8),
+            "u16" => BitContainer(16),
+            "u32" => BitContainer(32),
+            "u64" => BitContainer(64),
+            _ => return Err(Error::new(container.span(), MSG)),
+        })
+    }
+}
+
+/// Order of bit-level fields within their container.
+#[derive(Copy, Clone, Default, Eq, PartialEq)]
+pub enum BitOrder {
+    /// The first field occupies the least significant bits.
+    #[default]
+    LsbFirst,
+    /// The first field occupies the most significant bits.
+    MsbFirst,
+}
+
+impl TryFrom<&'_ LitStr> for BitOrder {
+    type Error = Error;
+    fn try_from(config: &LitStr) -> syn::Result<Self> {
+        const MSG: &str = "invalid bit order, must be one of `lsb`, `msb`";
+        Ok(match config.value().as_str() {
+            "lsb" => BitOrder::LsbFirst,
+            "msb" => BitOrder::MsbFirst,
+            _ => return Err(Error::new(config.span(), MSG)),
+        })
+    }
+}
+
 #[derive(Copy, Clone)]
 pub enum Direction {
     Encode,
@@ -300,6 +561,7 @@ impl Parse for Direction {
 
 pub struct ArgFieldDecl {
     pub name: Ident,
+    #[allow(dead_code)]
     pub colon_token: Token![:],
     pub r#type: Type,
     pub default_value: Option<Expr>,
@@ -318,17 +580,17 @@ impl Parse for ArgFieldDecl {
 
 pub struct ArgFieldAssign {
     pub name: Ident,
-    pub eq_token: Token![=],
-    pub value: Expr,
+    #[allow(dead_code)]
+    pub eq_token: Option<Token![=]>,
+    pub value: Option<Expr>,
 }
 
 impl Parse for ArgFieldAssign {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        Ok(ArgFieldAssign {
-            name: input.parse()?,
-            eq_token: input.parse()?,
-            value: input.parse()?,
-        })
+        let name = input.parse()?;
+        let eq_token: Option<Token![=]> = input.parse()?;
+        let value = eq_token.map(|_| input.parse()).transpose()?;
+        Ok(ArgFieldAssign { name, eq_token, value })
     }
 }
 
@@ -355,3 +617,179 @@ impl ToTokens for FieldKind {
         }
     }
 }
+
+/// Directives that can be put before or after something, e.g., `pad_before` for `@pad`.
+const DIRECTIVES: &[&str] = &["magic", "pad", "align", "align_from", "assert"];
+
+/// Attributes for `#[derive(BinData)]`, where temporaries and directives are also attributes.
+struct DeriveAttributes {
+    known_attrs: Vec<KnownAttribute>,
+    attrs: Vec<Attribute>,
+    entries: SurroundingEntries,
+}
+
+/// Entries specified in attributes, to be placed before and after something.
+#[derive(Default)]
+struct SurroundingEntries {
+    before: Vec<Entry>,
+    after: Vec<Entry>,
+}
+
+impl DeriveAttributes {
+    fn new(all_attrs: Vec<Attribute>, target: AttributeTarget) -> syn::Result<Self> {
+        let mut known_attrs = Vec::new();
+        let mut attrs = Vec::new();
+        let mut entries = SurroundingEntries::default();
+        for attr in all_attrs {
+            let Some(list) = bin_data_meta_list(&attr) else {
+                attrs.push(attr);
+                continue;
+            };
+            list.parse_args_with(|input: ParseStream| {
+                let cmd = Ident::parse_any(&input.fork())?;
+                let cmd_str = cmd.to_string();
+                if cmd_str == "temp" {
+                    let _: Ident = input.parse()?;
+                    let contents;
+                    parenthesized!(contents in input);
+                    entries.before.push(Entry::Field(Field::parse_temp(&contents, cmd)?));
+                } else if let Some(directive) = cmd_str.strip_suffix("_before").filter(|d| DIRECTIVES.contains(d)) {
+                    entries.before.push(Entry::Directive(Directive::parse_attribute(input, directive)?));
+                } else if let Some(directive) = cmd_str.strip_suffix("_after").filter(|d| DIRECTIVES.contains(d)) {
+                    entries.after.push(Entry::Directive(Directive::parse_attribute(input, directive)?));
+                } else {
+                    known_attrs.push(KnownAttribute::parse_with(input, target)?);
+                }
+                Ok(())
+            })?;
+        }
+        Ok(DeriveAttributes { known_attrs, attrs, entries })
+    }
+}
+
+impl SurroundingEntries {
+    fn is_empty(&self) -> bool { self.before.is_empty() && self.after.is_empty() }
+
+    fn into_body(self, fields: syn::Fields, span: Span) -> syn::Result<Body> {
+        let mut entries = Punctuated::new();
+        entries.extend(self.before);
+        for (index, field) in fields.iter().cloned().enumerate() {
+            let attrs = DeriveAttributes::new(field.attrs, AttributeTarget::Field)?;
+            let (member, name) = match field.ident {
+                Some(name) => (Member::Named(name.clone()), name),
+                None => (
+                    Member::Unnamed(Index { index: index as u32, span: field.ty.span() }),
+                    format_ident!("_{}", index, span = field.ty.span()),
+                ),
+            };
+            entries.extend(attrs.entries.before);
+            entries.push(Entry::Field(Field {
+                known_attrs: attrs.known_attrs,
+                attrs: attrs.attrs,
+                kind: FieldKind::Field(field.vis),
+                member,
+                name,
+                colon_token: field.colon_token,
+                r#type: field.ty,
+            }));
+            entries.extend(attrs.entries.after);
+        }
+        entries.extend(self.after);
+        Ok(match fields {
+            syn::Fields::Named(fields) => Body::Named { brace_token: fields.brace_token, entries },
+            syn::Fields::Unnamed(fields) => Body::Unnamed { paren_token: fields.paren_token, entries },
+            syn::Fields::Unit if entries.is_empty() => Body::Unit,
+            syn::Fields::Unit => Body::Named { brace_token: Brace(span), entries },
+        })
+    }
+}
+
+impl Directive {
+    fn parse_attribute(input: ParseStream, directive: &str) -> syn::Result<Self> {
+        let cmd: Ident = input.parse()?;
+        let directive = Ident::new(directive, cmd.span());
+        let at_token = Token![@](cmd.span());
+        if input.peek(Token![=]) {
+            let _: Token![=] = input.parse()?;
+            let argument: Expr = input.parse()?;
+            let paren_token = Paren(argument.span());
+            Ok(Directive { at_token, directive, paren_token, arguments: argument.into_token_stream() })
+        } else {
+            let arguments;
+            let paren_token = parenthesized!(arguments in input);
+            Ok(Directive { at_token, directive, paren_token, arguments: arguments.parse()? })
+        }
+    }
+}
+
+impl Field {
+    fn parse_temp(input: ParseStream, temp: Ident) -> syn::Result<Self> {
+        let name: Ident = input.parse()?;
+        let colon_token = input.parse()?;
+        let r#type = input.parse()?;
+        let mut known_attrs = Vec::new();
+        if input.peek(Token![=]) {
+            let _: Token![=] = input.parse()?;
+            known_attrs.push(KnownAttribute::Encode(input.parse()?));
+        }
+        while !input.is_empty() {
+            let _: Token![,] = input.parse()?;
+            if input.is_empty() { break; }
+            known_attrs.push(KnownAttribute::parse_with(input, AttributeTarget::Field)?);
+        }
+        Ok(Field {
+            known_attrs,
+            attrs: Vec::new(),
+            kind: FieldKind::Temp(Token![let](temp.span())),
+            member: Member::Named(name.clone()),
+            name,
+            colon_token: Some(colon_token),
+            r#type,
+        })
+    }
+}
+
+impl Variant {
+    fn from_derive(variant: syn::Variant) -> syn::Result<Self> {
+        let attrs = DeriveAttributes::new(variant.attrs, AttributeTarget::Variant)?;
+        let body = attrs.entries.into_body(variant.fields, variant.ident.span())?;
+        Ok(Variant {
+            known_attrs: attrs.known_attrs,
+            attrs: attrs.attrs,
+            name: variant.ident,
+            body,
+            discriminant: variant.discriminant,
+        })
+    }
+}
+
+impl TryFrom<DeriveInput> for Input {
+    type Error = Error;
+    fn try_from(input: DeriveInput) -> syn::Result<Self> {
+        let attrs = DeriveAttributes::new(input.attrs, AttributeTarget::Type)?;
+        let data = match input.data {
+            syn::Data::Struct(syn::DataStruct { struct_token, fields, semi_token }) => {
+                let body = attrs.entries.into_body(fields, input.ident.span())?;
+                Data::Struct { struct_token, body, semi_token }
+            }
+            syn::Data::Enum(syn::DataEnum { enum_token, brace_token, variants }) => {
+                if !attrs.entries.is_empty() {
+                    let msg = "directives and temporaries should be put on `enum` variants instead";
+                    return Err(Error::new(enum_token.span, msg));
+                }
+                let variants = variants.into_iter().map(Variant::from_derive).collect::<syn::Result<_>>()?;
+                Data::Enum { enum_token, brace_token, variants }
+            }
+            syn::Data::Union(syn::DataUnion { union_token, .. }) =>
+                return Err(Error::new(union_token.span, "`union`s are not supported")),
+        };
+        Ok(Input {
+            known_attrs: attrs.known_attrs,
+            attrs: attrs.attrs,
+            vis: input.vis,
+            name: input.ident,
+            generics: input.generics,
+            data,
+        })
+    }
+}
@@ -1,4 +1,125 @@
 //! Supporting macros for `bin_data`.
+//!
+//! # Syntax
+//!
+//! [`bin_data!`] takes a `struct` or `enum` whose fields are encoded in order, interleaved with
+//! temporaries and directives:
+//! ```
+//! # use bin_data::data::{Decode, Encode};
+//! # use bin_data_macros::bin_data;
+//! bin_data! {
+//!     #[derive(Debug, Eq, PartialEq)]
+//!     #[bin_data(endian = "little")]
+//!     pub struct Level {
+//!         @magic(*b"LV"),
+//!         #[bin_data(encode = waves.len() as u8)]
+//!         let wave_count: u8,
+//!         @assert(*wave_count <= 4, "too many waves"),
+//!         #[bin_data(args:decode { count = wave_count as usize })]
+//!         pub waves: Vec<u16>,
+//!     }
+//! }
+//! let level = Level::decode(&mut b"LV\x01\x2A\x00".as_ref()).unwrap();
+//! assert_eq!(level, Level { waves: vec![42] });
+//! ```
+//!
+//! ## Entries
+//!
+//! - `pub name: Type` is a field. In tuple structs and tuple variants, positional fields are
+//!   available as `_0`, `_1`, etc. in the expressions for `encode`, `decode`, and `args`.
+//! - `let name: Type` is a temporary, decoded like a field but not stored. Its value when
+//!   encoding is given by `#[bin_data(encode = ...)]`.
+//! - `@directive(...)` calls a method on the stream, see `Stream` and `StreamPosition` in
+//!   `bin_data::stream`, e.g., `@magic(...)`, `@pad(n)`, or `@align(n)`. The position where the
+//!   current `struct` or `enum` started is available as `struct_start`, e.g., in
+//!   `@align_from(struct_start, 4)`.
+//! - `@assert(condition, "message")` fails with `AssertionFailed` unless the condition holds, both
+//!   when decoding and encoding.
+//!
+//! A `struct` without any fields (e.g., one containing only directives) is emitted as a unit
+//! struct. In conditions of `@assert` and `if`, previous fields are references, so values of
+//! primitive types are compared with `*field`, e.g., `@assert(*count <= 8, "too many")`.
+//!
+//! ## Attributes
+//!
+//! On the type:
+//! - `#[bin_data(endian = "...")]`: one of `little`, `big`, `inherit` (from the parent), or
+//!   `none`. Fields may override it with their own `endian`.
+//! - `#[bin_data(args { name: Type, other: Type = default })]`: arguments, see below.
+//! - `#[bin_data(tag: Type)]`: the tag of an `enum`, encoded before the variant.
+//! - `#[bin_data(bit_order = "msb")]`: place bit-level fields from the most significant bit.
+//!
+//! On fields and temporaries:
+//! - `#[bin_data(encode = ...)]`: the value of a temporary when encoding.
+//! - `#[bin_data(decode = ...)]`: calculate the field when decoding, instead of reading it.
+//! - `#[bin_data(args { name = value, flag })]`: arguments of the field type, given to the
+//!   methods of its arguments builder. Use `args:decode` or `args:encode` for one direction only.
+//! - `#[bin_data(if = condition)]`: a field of type `Option<T>` is only present when the
+//!   condition holds, which may refer to previous fields. Encoding fails if `Some`/`None`
+//!   disagrees with the condition. Without `if`, the presence is determined by the arguments
+//!   instead, e.g., `args { flag }` for a presence byte before the value, see `OptionArgsBuilder`
+//!   in `bin_data::context`.
+//! - `#[bin_data(bits = N)]` and `#[bin_data(container = u16)]`: bit-level fields, see below.
+//!
+//! On `enum` variants:
+//! - `#[bin_data(tag = value)]`: the tag of the variant, see below.
+//! - `#[bin_data(unknown)]`: the variant capturing unknown tags, see below.
+//!
+//! ## Arguments
+//!
+//! Arguments declared on the type are collected into a generated `NameArgs` struct, built by a
+//! typed `NameArgsBuilder` with one setter per argument. Arguments without a default must be set
+//! before the builder is finished. When `args:decode` and `args:encode` are declared separately,
+//! the generated types are named `NameDecodeArgs` and `NameEncodeArgs` instead. Argument types may
+//! use the generic parameters of the type, e.g., `&'a Header`, and need not implement `Debug` or
+//! `Clone`.
+//!
+//! ## Enums
+//!
+//! The tag of a variant is given by `#[bin_data(tag = ...)]`, or else its discriminant, or else
+//! the previous tag plus one (starting at zero). Variants must have distinct tags. Instead of
+//! `tag: Type`, the tag may be passed by the parent as an argument, declared by
+//! `#[bin_data(args { tag: Type })]`, which the parent calculates by `Tagged::tag` for encoding.
+//!
+//! A tuple variant `Unknown(Tag)` annotated with `#[bin_data(unknown)]` captures tags not matching
+//! any other variant when decoding, instead of failing, and writes them back verbatim when
+//! encoding. Encoding fails if the raw tag belongs to another variant, which it would decode as.
+//!
+//! ## Bit-level fields
+//!
+//! Consecutive fields annotated with `#[bin_data(bits = N)]` are packed into a shared container
+//! integer, encoded with the endianness of the enclosing type. A container is closed as soon as its
+//! fields add up to 8, 16, 32, or 64 bits, unless `#[bin_data(container = u16)]` (or `u8`, `u32`,
+//! `u64`) on the first field of a group chooses a container explicitly, e.g., when a big-endian
+//! `u16` starts with fields adding up to 8 bits. Fields are placed from the least significant bit
+//! by default. Encoding fails if a value does not fit in its bit width.
+//!
+//! ## Generated implementations
+//!
+//! A `struct` whose layout does not depend on the values (no `if` fields, and only `@magic`,
+//! `@pad`, and `@assert` directives, with `@magic` and `@pad` not mentioning fields, temporaries,
+//! or arguments) implements `FixedSize` whenever all its field types do.
+//!
+//! Without directives depending on the position (e.g., `@align`), `Encode::encoded_size_with` is
+//! calculated from the sizes of the fields, instead of encoding to a byte counter. Either way, the
+//! fields are sized at their positions in the output (see `Encode::encoded_size_at`), so nested
+//! data using `@align` is counted correctly.
+//!
+//! A type with a lifetime parameter implements `DecodeBorrowed` instead of `Decode`, decoding from
+//! an in-memory byte slice. Its fields (e.g., `&'a str`, `&'a [u8]`, or `UnalignedSlice<'a, T>`)
+//! may then borrow from the input, using the first lifetime parameter for the input. Other types
+//! implement both, so they can be used as fields of borrowed types.
+//!
+//! ## Derive
+//!
+//! [`BinData`](derive@BinData) accepts the same attributes on a normal `struct` or `enum`, but
+//! temporaries and directives are specified as attributes on the fields:
+//! - `#[bin_data(temp(name: Type = encode_expr, other_options...))]` for a temporary field
+//!   `let name: Type` before the annotated field, with `#[bin_data(encode = encode_expr)]`.
+//! - `#[bin_data(pad_before = 3)]` for a directive `@pad(3)` before the annotated field, and
+//!   `#[bin_data(magic_after(...))]` for a directive `@magic(...)` after that field.
+//!
+//! On the `struct` itself (or on `enum` variants), they are placed at the beginning or the end.
 
 #![warn(missing_docs)]
 
@@ -6,24 +127,36 @@ mod input;
 mod code_gen;
 
 use proc_macro2::TokenStream;
-use syn::parse_macro_input;
-use crate::code_gen::{extract_args, extract_struct, impl_decode, impl_encode};
-use crate::input::{Entry, Input};
+use syn::{parse_macro_input, DeriveInput};
+use crate::code_gen::{extract_args, extract_type, impl_decode, impl_encode, impl_fixed_size};
+use crate::input::Input;
 
-/// Declare a binary data format.
+/// Declare a binary data format, see the [crate documentation](crate) for the syntax.
 #[proc_macro]
 pub fn bin_data(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as Input);
     let mut result = TokenStream::new();
-    extract_struct(&input, &mut result);
+    extract_type(&input, &mut result);
     let args = extract_args(&input.known_attrs);
-    let field_args = input.entries.iter()
-        .map(|entry| match entry {
-            Entry::Directive(_) => None,
-            Entry::Field(field) => Some(extract_args(&field.known_attrs)),
-        })
-        .collect::<Vec<_>>();
-    impl_decode(&input, &args, &field_args, &mut result);
-    impl_encode(&input, &args, &field_args, &mut result);
+    impl_decode(&input, &args, &mut result);
+    impl_encode(&input, &args, &mut result);
+    impl_fixed_size(&input, &args, &mut result);
+    result.into()
+}
+
+/// Derive the binary data format for a normal `struct` or `enum`, see the
+/// [crate documentation](crate#derive) for the attributes.
+#[proc_macro_derive(BinData, attributes(bin_data))]
+pub fn derive_bin_data(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let input = match Input::try_from(input) {
+        Ok(input) => input,
+        Err(err) => return err.into_compile_error().into(),
+    };
+    let mut result = TokenStream::new();
+    let args = extract_args(&input.known_attrs);
+    impl_decode(&input, &args, &mut result);
+    impl_encode(&input, &args, &mut result);
+    impl_fixed_size(&input, &args, &mut result);
     result.into()
 }
@@ -0,0 +1,121 @@
+use std::io::{Read, Write};
+use bin_data::context::{Context, NoArgs, NoEndian};
+use bin_data::data::{Decode, Encode};
+use bin_data::stream::{dir, DecodeError, Direction, EncodeError, PositionTracker, Stream, StreamPosition};
+use bin_data_macros::{bin_data, BinData};
+
+bin_data! {
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Entry {
+        pub id: u8,
+        @align_from(struct_start, 4),
+        pub offset: u32,
+        pub flag: u8,
+        @align_from(struct_start, 4),
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Archive {
+        @magic(*b"ARC"),
+        @align(16),
+        pub header: u16,
+        pub first: Entry,
+        @align(16),
+        pub second: Entry,
+    }
+}
+
+#[derive(BinData, Debug, Copy, Clone, Eq, PartialEq)]
+#[bin_data(endian = "big")]
+pub struct Sector {
+    #[bin_data(align_after = 512)]
+    pub tag: u16,
+}
+
+/// Hand-written, only implementing the methods on plain streams.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Marker;
+
+impl<Dir: Direction> Context<Dir> for Marker {
+    type EndianContext = NoEndian;
+    type ArgsBuilder = NoArgs;
+    fn args_builder() -> NoArgs { NoArgs }
+}
+
+impl Decode for Marker {
+    fn decode_with<R: Read + ?Sized>(reader: &mut R, _: NoEndian, _: ()) -> Result<Self, DecodeError> {
+        Stream::<dir::Read>::magic(reader, b'M').map(|_| Marker)
+    }
+}
+
+impl Encode for Marker {
+    fn encode_with<W: Write + ?Sized>(&self, writer: &mut W, _: NoEndian, _: ()) -> Result<(), EncodeError> {
+        Stream::<dir::Write>::magic(writer, b'M')
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Marked {
+        pub markers: [Marker; 2],
+        @align(4),
+        pub value: u8,
+    }
+}
+
+#[test]
+fn test_align() {
+    let archive = Archive {
+        header: 0x0102,
+        first: Entry { id: 1, offset: 0x11223344, flag: 7 },
+        second: Entry { id: 2, offset: 0, flag: 1 },
+    };
+    let mut output = Vec::new();
+    archive.encode(&mut output).unwrap();
+    let mut expected = b"ARC".to_vec();
+    expected.resize(16, 0);
+    // header and `first` start at 18, so `first` is aligned relative to itself
+    expected.extend([0x02, 0x01]);
+    expected.extend([1, 0, 0, 0, 0x44, 0x33, 0x22, 0x11, 7, 0, 0, 0]);
+    expected.resize(32, 0);
+    expected.extend([2, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0]);
+    assert_eq!(output, expected);
+    assert_eq!(Archive::decode(&mut output.as_slice()).unwrap(), archive);
+}
+
+#[test]
+fn test_align_derive() {
+    let mut output = Vec::new();
+    Sector { tag: 0xABCD }.encode(&mut output).unwrap();
+    assert_eq!(output.len(), 512);
+    assert_eq!(output[..2], [0xAB, 0xCD]);
+    assert!(output[2..].iter().all(|&b| b == 0));
+    assert_eq!(Sector::decode(&mut output.as_slice()).unwrap(), Sector { tag: 0xABCD });
+}
+
+#[test]
+fn test_align_hand_written() {
+    let marked = Marked { markers: [Marker; 2], value: 42 };
+    let mut output = Vec::new();
+    marked.encode_with(&mut output, NoEndian, ()).unwrap();
+    assert_eq!(output, b"MM\0\0*");
+    assert_eq!(Marked::decode_with(&mut output.as_slice(), NoEndian, ()).unwrap(), marked);
+}
+
+#[test]
+fn test_align_from_future_origin() {
+    let mut reader = PositionTracker::new([0_u8; 8].as_ref());
+    let err = reader.align_from(4, 4).unwrap_err();
+    assert!(matches!(err, DecodeError::InvalidData("alignment origin")));
+    let mut writer = PositionTracker::new(Vec::new());
+    let err = writer.align_from(4, 4).unwrap_err();
+    assert!(matches!(err, EncodeError::InvalidData("alignment origin")));
+    assert!(writer.into_inner().is_empty());
+}
+
+fn main() {}
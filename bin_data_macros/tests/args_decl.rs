@@ -0,0 +1,114 @@
+use bin_data::context::NoEndian;
+use bin_data::data::{Decode, DecodeBorrowed, Encode};
+use bin_data::stream::PositionTracker;
+use bin_data_macros::bin_data;
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "inherit")]
+    #[bin_data(args { version: u16, count: usize = 2 })]
+    pub struct Wave {
+        #[bin_data(encode = version)]
+        let stored_version: u16,
+        #[bin_data(decode = stored_version == version)]
+        pub up_to_date: bool,
+        #[bin_data(args:decode { count = count })]
+        pub zombies: Vec<u8>,
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Level {
+        #[bin_data(encode = 3)]
+        let version: u16,
+        #[bin_data(encode = waves.len() as u8)]
+        let wave_count: u8,
+        #[bin_data(args:decode { count = wave_count as usize })]
+        #[bin_data(args { arg = WaveArgs { version, count: 2 } })]
+        pub waves: Vec<Wave>,
+        #[bin_data(args { version = version, count = 3 })]
+        pub final_wave: Wave,
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    #[bin_data(args:decode { scale: u8 })]
+    #[bin_data(args:encode { divisor: u8 = 1 })]
+    pub struct Scaled {
+        #[bin_data(encode = *value / divisor)]
+        let raw: u8,
+        #[bin_data(decode = raw * scale)]
+        pub value: u8,
+    }
+}
+
+/// Neither `Debug` nor `Clone`.
+pub struct Palette {
+    pub colors: Vec<u32>,
+}
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    #[bin_data(args { palette: &'a Palette })]
+    pub struct Sprite<'a> {
+        #[bin_data(encode = palette.colors.iter().position(|&c| c == *color).unwrap() as u8)]
+        let index: u8,
+        #[bin_data(decode = palette.colors[index as usize])]
+        pub color: u32,
+        #[bin_data(encode = name.len() as u8)]
+        let name_length: u8,
+        #[bin_data(args:decode { count = name_length as usize })]
+        pub name: &'a str,
+    }
+}
+
+#[test]
+fn test_nested_args() {
+    let input = [
+        3, 0, // version
+        1, // wave count
+        3, 0, 10, 11, // waves[0]
+        2, 0, 20, 21, 22, // final wave
+    ];
+    let decoded = Level::decode(&mut input.as_ref()).unwrap();
+    let expected = Level {
+        waves: vec![Wave { up_to_date: true, zombies: vec![10, 11] }],
+        final_wave: Wave { up_to_date: false, zombies: vec![20, 21, 22] },
+    };
+    assert_eq!(decoded, expected);
+    let mut output = Vec::new();
+    decoded.encode(&mut output).unwrap();
+    let expected = [3, 0, 1, 3, 0, 10, 11, 3, 0, 20, 21, 22];
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_separate_args() {
+    let decoded = Scaled::decode_with(&mut [7].as_ref(), NoEndian, ScaledDecodeArgs { scale: 3 }).unwrap();
+    assert_eq!(decoded, Scaled { value: 21 });
+    let mut output = Vec::new();
+    decoded.encode(&mut output).unwrap();
+    assert_eq!(output, [21]);
+    let mut output = Vec::new();
+    decoded.encode_with(&mut output, NoEndian, ScaledEncodeArgs { divisor: 3 }).unwrap();
+    assert_eq!(output, [7]);
+}
+
+#[test]
+fn test_borrowed_args() {
+    let palette = Palette { colors: vec![0x000000, 0xFF8000] };
+    let input = b"\x01\x03pea";
+    let mut reader = PositionTracker::new(input.as_ref());
+    let sprite = Sprite::decode_borrowed_with(&mut reader, NoEndian, SpriteArgs { palette: &palette }).unwrap();
+    assert_eq!(sprite, Sprite { color: 0xFF8000, name: "pea" });
+    let mut output = Vec::new();
+    sprite.encode_with(&mut output, NoEndian, SpriteArgs { palette: &palette }).unwrap();
+    assert_eq!(output, input);
+}
+
+fn main() {}
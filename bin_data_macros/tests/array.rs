@@ -0,0 +1,55 @@
+use bin_data::context::BoolWidth;
+use bin_data::data::{Decode, Encode, FixedSize, FixedStr};
+use bin_data::stream::{DecodeError, EncodeError};
+use bin_data_macros::bin_data;
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Level {
+        pub costs: [u16; 4],
+        #[bin_data(args { arg = BoolWidth::U16 })]
+        #[bin_data(args { map_arg = |width| bin_data::context::BoolArgsBuilder::default().width(width).finish() })]
+        pub unlocked: [bool; 2],
+        #[bin_data(args { args = [b' ', b'.'], map_arg = |padding| bin_data::context::FixedStrArgs { padding } })]
+        pub names: [FixedStr<4>; 2],
+        pub grid: [i8; 6],
+    }
+}
+
+#[test]
+fn test_array() {
+    let input = b"\x32\x00\x64\x00\x96\x00\xAF\x00\x01\x00\x00\x00pea sun.\x01\x02\x03\x04\x05\x06";
+    let level = Level::decode(&mut input.as_ref()).unwrap();
+    assert_eq!(level, Level {
+        costs: [50, 100, 150, 175],
+        unlocked: [true, false],
+        names: [FixedStr("pea".to_string()), FixedStr("sun".to_string())],
+        grid: [1, 2, 3, 4, 5, 6],
+    });
+    let mut output = Vec::new();
+    level.encode(&mut output).unwrap();
+    assert_eq!(output, input);
+    assert_eq!(level.encoded_size().unwrap(), input.len() as u64);
+    assert_eq!(<[[u16; 3]; 2]>::ENCODED_SIZE, 12);
+}
+
+#[test]
+fn test_array_errors() {
+    let err = Level::decode(&mut [0; 22].as_ref()).unwrap_err();
+    assert_eq!(err.path().unwrap().to_string(), "Level.grid[2]");
+    assert_eq!(err.offset(), Some(22));
+    assert!(matches!(err.root_cause(), DecodeError::IncompleteData(..)));
+
+    let level = Level {
+        costs: [0; 4],
+        unlocked: [false; 2],
+        names: [FixedStr("wallnut".to_string()), FixedStr::default()],
+        grid: [0; 6],
+    };
+    let err = level.encode(&mut Vec::new()).unwrap_err();
+    assert_eq!(err.path().unwrap().to_string(), "Level.names[0]");
+    assert!(matches!(err.root_cause(), EncodeError::InvalidArgument("FixedStr", _)));
+}
+
+fn main() {}
@@ -0,0 +1,104 @@
+use bin_data::data::{Decode, Encode};
+use bin_data::stream::{DecodeError, EncodeError};
+use bin_data_macros::{bin_data, BinData};
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Header {
+        pub header_size: u32,
+        @assert(*header_size >= 8, "header too small"),
+        #[bin_data(encode = lanes.len() as u8)]
+        let lane_count: u8,
+        @assert(*lane_count <= 6, "too many lanes"),
+        #[bin_data(args:decode { count = lane_count as usize })]
+        pub lanes: Vec<u8>,
+        @assert(lanes.iter().all(|&lane| lane < 3), "unknown lane kind"),
+    }
+}
+
+pub type Lane = u8;
+
+bin_data! {
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Spawn {
+        pub lane: Lane,
+        @assert(*lane < 6, "invalid lane"),
+        pub delay: std::primitive::u16,
+        #[bin_data(if = *delay > 0)]
+        pub wave: Option<Lane>,
+    }
+}
+
+#[derive(BinData, Debug, Copy, Clone, Eq, PartialEq)]
+#[bin_data(endian = "big")]
+pub struct Range {
+    pub start: u16,
+    #[bin_data(assert_after(start <= end, "empty range"))]
+    pub end: u16,
+}
+
+#[test]
+fn test_assert_holds() {
+    let header = Header { header_size: 8, lanes: vec![0, 2, 1] };
+    let mut output = Vec::new();
+    header.encode(&mut output).unwrap();
+    assert_eq!(output, [8, 0, 0, 0, 3, 0, 2, 1]);
+    assert_eq!(Header::decode(&mut output.as_slice()).unwrap(), header);
+}
+
+#[test]
+fn test_assert_decode() {
+    let err = Header::decode(&mut [4, 0, 0, 0, 0].as_ref()).unwrap_err();
+    assert_eq!(err.offset(), Some(0));
+    assert!(matches!(err.root_cause(), DecodeError::AssertionFailed {
+        message: "header too small",
+        expression: "*header_size >= 8",
+    }));
+    assert_eq!(err.root_cause().to_string(), "assertion failed: header too small (`*header_size >= 8`)");
+
+    let err = Header::decode(&mut [8, 0, 0, 0, 7].as_ref()).unwrap_err();
+    assert!(matches!(err.root_cause(), DecodeError::AssertionFailed { message: "too many lanes", .. }));
+    let err = Header::decode(&mut [8, 0, 0, 0, 2, 1, 5].as_ref()).unwrap_err();
+    assert!(matches!(err.root_cause(), DecodeError::AssertionFailed {
+        message: "unknown lane kind",
+        expression: "lanes.iter().all(|&lane| lane < 3)",
+    }));
+
+    let err = Range::decode(&mut [0, 2, 0, 1].as_ref()).unwrap_err();
+    assert!(matches!(err.root_cause(), DecodeError::AssertionFailed {
+        message: "empty range",
+        expression: "start <= end",
+    }));
+}
+
+#[test]
+fn test_assert_encode() {
+    let header = Header { header_size: 8, lanes: vec![0; 7] };
+    let err = header.encode(&mut Vec::new()).unwrap_err();
+    assert!(matches!(err, EncodeError::AssertionFailed { message: "too many lanes", .. }));
+
+    let mut output = Vec::new();
+    Range { start: 1, end: 2 }.encode(&mut output).unwrap();
+    assert_eq!(output, [0, 1, 0, 2]);
+    let err = Range { start: 2, end: 1 }.encode(&mut Vec::new()).unwrap_err();
+    assert!(matches!(err, EncodeError::AssertionFailed { message: "empty range", .. }));
+}
+
+#[test]
+fn test_assert_type_alias() {
+    let spawn = Spawn { lane: 5, delay: 1, wave: Some(2) };
+    let mut output = Vec::new();
+    spawn.encode(&mut output).unwrap();
+    assert_eq!(output, [5, 1, 0, 2]);
+    assert_eq!(Spawn::decode(&mut output.as_slice()).unwrap(), spawn);
+    assert_eq!(Spawn::decode(&mut [0, 0, 0].as_ref()).unwrap(), Spawn { lane: 0, delay: 0, wave: None });
+
+    let err = Spawn::decode(&mut [6, 0, 0].as_ref()).unwrap_err();
+    assert!(matches!(err.root_cause(), DecodeError::AssertionFailed { message: "invalid lane", .. }));
+    let err = Spawn { lane: 6, delay: 0, wave: None }.encode(&mut Vec::new()).unwrap_err();
+    assert!(matches!(err, EncodeError::AssertionFailed { message: "invalid lane", .. }));
+}
+
+fn main() {}
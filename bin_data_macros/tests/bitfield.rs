@@ -0,0 +1,103 @@
+use bin_data::data::{Decode, Encode};
+use bin_data::stream::EncodeError;
+use bin_data_macros::{bin_data, BinData};
+
+bin_data! {
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Header {
+        #[bin_data(bits = 3)]
+        pub kind: u8,
+        #[bin_data(bits = 5)]
+        pub level: u8,
+        #[bin_data(bits = 4)]
+        pub row: u8,
+        #[bin_data(bits = 12)]
+        pub column: u16,
+        pub trailer: u8,
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "big")]
+    #[bin_data(bit_order = "msb")]
+    pub struct Flags {
+        #[bin_data(bits = 1)]
+        pub enabled: u8,
+        #[bin_data(bits = 3)]
+        #[bin_data(encode = 0)]
+        let _reserved: u8,
+        #[bin_data(bits = 12)]
+        pub value: u16,
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "big")]
+    pub struct Word {
+        #[bin_data(bits = 3)]
+        #[bin_data(container = u16)]
+        pub kind: u8,
+        #[bin_data(bits = 5)]
+        pub level: u8,
+        #[bin_data(bits = 8)]
+        pub value: u8,
+    }
+}
+
+#[derive(BinData, Debug, Copy, Clone, Eq, PartialEq)]
+#[bin_data(endian = "little")]
+pub struct Nibbles(
+    #[bin_data(bits = 4)] pub u8,
+    #[bin_data(bits = 4)] pub u8,
+);
+
+#[test]
+fn test_lsb_first() {
+    let header = Header { kind: 5, level: 17, row: 9, column: 0xABC, trailer: 0xFF };
+    let mut output = Vec::new();
+    header.encode(&mut output).unwrap();
+    assert_eq!(output, [0b1000_1101, 0b1100_1001, 0xAB, 0xFF]);
+    assert_eq!(Header::decode(&mut output.as_slice()).unwrap(), header);
+}
+
+#[test]
+fn test_explicit_container_big_endian() {
+    let word = Word { kind: 5, level: 17, value: 0xAB };
+    let mut output = Vec::new();
+    word.encode(&mut output).unwrap();
+    assert_eq!(output, [0xAB, 0b1000_1101]);
+    assert_eq!(Word::decode(&mut output.as_slice()).unwrap(), word);
+}
+
+#[test]
+fn test_msb_first_big_endian() {
+    let flags = Flags { enabled: 1, value: 0x123 };
+    let mut output = Vec::new();
+    flags.encode(&mut output).unwrap();
+    assert_eq!(output, [0x81, 0x23]);
+    assert_eq!(Flags::decode(&mut output.as_slice()).unwrap(), flags);
+    assert_eq!(Flags::decode(&mut [0xF1, 0x23].as_ref()).unwrap(), flags);
+}
+
+#[test]
+fn test_derive_bitfield() {
+    let nibbles = Nibbles::decode(&mut [0x4A].as_ref()).unwrap();
+    assert_eq!(nibbles, Nibbles(0xA, 0x4));
+}
+
+#[test]
+fn test_value_overflow() {
+    let header = Header { kind: 8, level: 0, row: 0, column: 0, trailer: 0 };
+    let err = header.encode(&mut Vec::new()).unwrap_err();
+    assert!(matches!(err.root_cause(), EncodeError::InvalidData("kind")));
+    assert_eq!(err.path().unwrap().to_string(), "Header.kind");
+
+    let flags = Flags { enabled: 0, value: 0x1000 };
+    let err = flags.encode(&mut Vec::new()).unwrap_err();
+    assert!(matches!(err.root_cause(), EncodeError::InvalidData("value")));
+}
+
+fn main() {}
@@ -0,0 +1,41 @@
+use bin_data::context::BoolWidth;
+use bin_data::data::{Decode, Encode};
+use bin_data::stream::DecodeError;
+use bin_data_macros::bin_data;
+
+bin_data! {
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "big")]
+    pub struct Options {
+        pub sound: bool,
+        #[bin_data(args { width = BoolWidth::U16 })]
+        pub music: bool,
+        #[bin_data(args { width = BoolWidth::U32, strict = false })]
+        pub fullscreen: bool,
+        @assert(!*fullscreen || *sound, "fullscreen without sound"),
+    }
+}
+
+#[test]
+fn test_bool() {
+    let input = [1, 0, 0, 0, 0, 0, 0xFF];
+    let options = Options::decode(&mut input.as_ref()).unwrap();
+    assert_eq!(options, Options { sound: true, music: false, fullscreen: true });
+    let mut output = Vec::new();
+    options.encode(&mut output).unwrap();
+    assert_eq!(output, [1, 0, 0, 0, 0, 0, 1]);
+    assert_eq!(options.encoded_size().unwrap(), 7);
+}
+
+#[test]
+fn test_bool_strict() {
+    let err = Options::decode(&mut [2, 0, 0, 0, 0, 0, 0].as_ref()).unwrap_err();
+    assert_eq!(err.path().unwrap().to_string(), "Options.sound");
+    assert!(matches!(err.root_cause(), DecodeError::InvalidData("bool")));
+
+    let err = Options::decode(&mut [1, 1, 0, 0, 0, 0, 0].as_ref()).unwrap_err();
+    assert_eq!(err.path().unwrap().to_string(), "Options.music");
+    assert_eq!(err.offset(), Some(1));
+}
+
+fn main() {}
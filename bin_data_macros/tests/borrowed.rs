@@ -0,0 +1,164 @@
+use bin_data::data::{DecodeBorrowed, Encode, UnalignedSlice};
+use bin_data::stream::{DecodeError, PositionTracker};
+use bin_data_macros::{bin_data, BinData};
+
+bin_data! {
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "big")]
+    pub struct Header {
+        @magic(*b"PAK"),
+        pub version: u8,
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Clone)]
+    #[bin_data(endian = "big")]
+    pub struct Asset<'a> {
+        #[bin_data(encode = name.len() as u8)]
+        let name_length: u8,
+        #[bin_data(args:decode { count = name_length as usize })]
+        pub name: &'a str,
+        #[bin_data(encode = data.len() as u16)]
+        let data_length: u16,
+        #[bin_data(args:decode { count = data_length as usize })]
+        pub data: &'a [u8],
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Clone)]
+    #[bin_data(endian = "big")]
+    pub struct Pack<'a> {
+        pub header: Header,
+        pub asset: Asset<'a>,
+        #[bin_data(endian = "little")]
+        #[bin_data(args:decode { count = 3 })]
+        pub palette: UnalignedSlice<'a, u16>,
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Clone)]
+    #[bin_data(endian = "big")]
+    pub struct Catalog<'a> {
+        pub header: Header,
+        #[bin_data(encode = assets.len() as u8)]
+        let asset_count: u8,
+        #[bin_data(args:decode { count = asset_count as usize })]
+        pub assets: Vec<Asset<'a>>,
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Copy, Clone)]
+    #[bin_data(endian = "big")]
+    pub struct Tag<'a> {
+        #[bin_data(args:decode { count = 2 })]
+        pub code: &'a [u8],
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Clone)]
+    #[bin_data(endian = "big")]
+    pub struct Tags<'a> {
+        #[bin_data(args:decode { until_eof })]
+        pub tags: Vec<Tag<'a>>,
+    }
+}
+
+#[derive(BinData, Debug, Clone)]
+#[bin_data(endian = "little")]
+#[bin_data(tag: u8)]
+pub enum Chunk<'a> {
+    #[bin_data(tag = 0)]
+    Empty,
+    #[bin_data(tag = 1)]
+    Text(
+        #[bin_data(temp(length: u8 = _0.len() as u8))]
+        #[bin_data(args:decode { count = length as usize })]
+        &'a str,
+    ),
+}
+
+#[test]
+fn test_borrowed_decode() {
+    let input = b"PAK\x02\x04icon\x00\x03\xFF\x00\xFF\x01\x00\x02\x00\x03\x00rest";
+    let mut remaining = input.as_ref();
+    let pack = Pack::decode_borrowed(&mut remaining).unwrap();
+    assert_eq!(remaining, b"rest");
+    assert_eq!(pack.header, Header { version: 2 });
+    assert_eq!(pack.asset.name, "icon");
+    assert_eq!(pack.asset.data, [0xFF, 0x00, 0xFF]);
+    assert_eq!(pack.palette.len(), 3);
+    assert_eq!(pack.palette.iter().collect::<Vec<_>>(), [1, 2, 3]);
+    // borrowed from the input, not copied
+    assert_eq!(pack.asset.name.as_ptr(), input[5..].as_ptr());
+    assert_eq!(pack.palette.as_bytes().as_ptr(), input[14..].as_ptr());
+
+    let mut output = Vec::new();
+    pack.encode(&mut output).unwrap();
+    assert_eq!(output, input[..input.len() - 4]);
+}
+
+#[test]
+fn test_borrowed_vec() {
+    let input = b"PAK\x01\x02\x01a\x00\x01\xAA\x02bc\x00\x00";
+    let catalog = Catalog::decode_borrowed(&mut input.as_ref()).unwrap();
+    assert_eq!(catalog.header, Header { version: 1 });
+    let names: Vec<_> = catalog.assets.iter().map(|asset| asset.name).collect();
+    assert_eq!(names, ["a", "bc"]);
+    assert_eq!(catalog.assets[0].data, [0xAA]);
+    assert!(catalog.assets[1].data.is_empty());
+    assert_eq!(catalog.assets[1].name.as_ptr(), input[11..].as_ptr());
+
+    let mut output = Vec::new();
+    catalog.encode(&mut output).unwrap();
+    assert_eq!(output, input);
+}
+
+#[test]
+fn test_borrowed_until_eof() {
+    let input = b"abcdef";
+    let tags = Tags::decode_borrowed(&mut input.as_ref()).unwrap();
+    let codes: Vec<_> = tags.tags.iter().map(|tag| tag.code).collect();
+    assert_eq!(codes, [b"ab", b"cd", b"ef"]);
+    assert_eq!(tags.tags[2].code.as_ptr(), input[4..].as_ptr());
+}
+
+#[test]
+fn test_borrowed_enum() {
+    let chunk = Chunk::decode_borrowed(&mut [1, 2, b'h', b'i'].as_ref()).unwrap();
+    assert!(matches!(chunk, Chunk::Text("hi")));
+    let chunk = Chunk::decode_borrowed(&mut [0].as_ref()).unwrap();
+    assert!(matches!(chunk, Chunk::Empty));
+}
+
+#[test]
+fn test_borrowed_errors() {
+    let err = Pack::decode_borrowed(&mut b"PAK\x02\x04ic".as_ref()).unwrap_err();
+    assert_eq!(err.offset(), Some(5));
+    assert_eq!(err.path().unwrap().to_string(), "Pack.asset.name");
+    assert!(matches!(err.root_cause(), DecodeError::IncompleteData("&str", _)));
+
+    let err = Asset::decode_borrowed(&mut b"\x03ic\xFF\x00\x00".as_ref()).unwrap_err();
+    assert!(matches!(err.root_cause(), DecodeError::DecodeUtf8Error { valid_prefix, invalid_bytes }
+        if &**valid_prefix == "ic" && **invalid_bytes == [0xFF]));
+}
+
+#[test]
+fn test_borrow_after_read_ahead() {
+    let mut reader = PositionTracker::new(b"PAK".as_ref());
+    assert!(!reader.at_end().unwrap());
+    let err = reader.borrow_bytes(2).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert_eq!(reader.position(), 0);
+
+    let mut reader = PositionTracker::new(b"PAK".as_ref());
+    assert!(!reader.is_exhausted());
+    assert_eq!(reader.borrow_bytes(3).unwrap(), b"PAK");
+    assert!(reader.is_exhausted());
+}
+
+fn main() {}
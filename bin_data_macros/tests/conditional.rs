@@ -0,0 +1,88 @@
+use bin_data::data::{Decode, Encode};
+use bin_data::stream::EncodeError;
+use bin_data_macros::{bin_data, BinData};
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Zombie {
+        pub flags: u8,
+        #[bin_data(if = flags & 0x01 != 0)]
+        pub health: Option<u16>,
+        #[bin_data(if = flags & 0x02 != 0)]
+        #[bin_data(args:decode { count = 2 })]
+        pub position: Option<Vec<u8>>,
+        pub speed: u8,
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "big")]
+    pub struct Versioned {
+        #[bin_data(encode = if extra.is_some() { 2 } else { 1 })]
+        let version: u8,
+        #[bin_data(if = *version >= 2)]
+        pub extra: Option<u32>,
+    }
+}
+
+#[derive(BinData, Debug, Copy, Clone, Eq, PartialEq)]
+#[bin_data(endian = "little")]
+pub struct Derived {
+    pub has_value: u8,
+    #[bin_data(if = has_value & 1 != 0)]
+    pub value: Option<u16>,
+}
+
+#[test]
+fn test_present_and_absent() {
+    let input = [0x03, 0x2C, 0x01, 7, 8, 5];
+    let decoded = Zombie::decode(&mut input.as_ref()).unwrap();
+    assert_eq!(decoded, Zombie { flags: 3, health: Some(300), position: Some(vec![7, 8]), speed: 5 });
+    let mut output = Vec::new();
+    decoded.encode(&mut output).unwrap();
+    assert_eq!(output, input);
+
+    let input = [0x02, 7, 8, 5];
+    let decoded = Zombie::decode(&mut input.as_ref()).unwrap();
+    assert_eq!(decoded, Zombie { flags: 2, health: None, position: Some(vec![7, 8]), speed: 5 });
+    let mut output = Vec::new();
+    decoded.encode(&mut output).unwrap();
+    assert_eq!(output, input);
+}
+
+#[test]
+fn test_condition_on_temporary() {
+    let versioned = Versioned { extra: Some(42) };
+    let mut output = Vec::new();
+    versioned.encode(&mut output).unwrap();
+    assert_eq!(output, [2, 0, 0, 0, 42]);
+    assert_eq!(Versioned::decode(&mut output.as_slice()).unwrap(), versioned);
+
+    assert_eq!(Versioned::decode(&mut [1].as_ref()).unwrap(), Versioned { extra: None });
+}
+
+#[test]
+fn test_condition_mismatch() {
+    let zombie = Zombie { flags: 0, health: Some(1), position: None, speed: 0 };
+    let err = zombie.encode(&mut Vec::new()).unwrap_err();
+    assert!(matches!(err.root_cause(), EncodeError::InvalidData("health")));
+    assert_eq!(err.to_string(), "failed to encode 'Zombie.health'");
+
+    let zombie = Zombie { flags: 2, health: None, position: None, speed: 0 };
+    let err = zombie.encode(&mut Vec::new()).unwrap_err();
+    assert!(matches!(err.root_cause(), EncodeError::InvalidData("position")));
+}
+
+#[test]
+fn test_derive_conditional() {
+    let input = [1, 0x34, 0x12];
+    let decoded = Derived::decode(&mut input.as_ref()).unwrap();
+    assert_eq!(decoded, Derived { has_value: 1, value: Some(0x1234) });
+    let mut output = Vec::new();
+    decoded.encode(&mut output).unwrap();
+    assert_eq!(output, input);
+}
+
+fn main() {}
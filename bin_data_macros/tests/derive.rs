@@ -0,0 +1,105 @@
+use bin_data::data::{Decode, Encode, SliceView};
+use bin_data_macros::BinData;
+
+#[derive(Debug, Copy, Clone, PartialEq, BinData)]
+#[bin_data(endian = "little")]
+#[bin_data(magic_after([0x12, 0x34, 0x56, 0x78]))]
+pub struct Test {
+    #[bin_data(endian = "big")]
+    some_private_field: i64,
+    pub some_pub_field: u8,
+    #[bin_data(pad_after = 3)]
+    pub(in self) some_fancy_visibility: u32,
+    #[bin_data(temp(temporary: f32 = *move_data))]
+    #[bin_data(decode = temporary)]
+    pub move_data: f32,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, BinData)]
+#[bin_data(endian = "little")]
+pub struct Values {
+    #[bin_data(temp(length: u32 = values.len() as u32))]
+    #[bin_data(temp(
+        xs: Vec<u8> = SliceView::new(values, |&(x, _)| x),
+        args:decode { count = length as usize },
+    ))]
+    #[bin_data(temp(
+        ys: Vec<u16> = SliceView::new(values, |&(_, y)| y),
+        args:decode { count = length as usize },
+    ))]
+    #[bin_data(decode = std::iter::zip(xs, ys).collect())]
+    pub values: Box<[(u8, u16)]>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, BinData)]
+#[bin_data(endian = "big")]
+#[bin_data(tag: u8)]
+pub enum Command {
+    #[bin_data(tag = 0x10)]
+    Move { x: i16, y: i16 },
+    #[bin_data(tag = 0x20)]
+    #[bin_data(magic_before = *b"STOP")]
+    Stop,
+    Nop,
+}
+
+const EXAMPLE: Test = Test {
+    some_private_field: 0x11_22_33_44_55_66_77_88,
+    some_pub_field: 42,
+    some_fancy_visibility: 0xDEAD_BEEF,
+    move_data: std::f32::consts::PI,
+};
+
+#[test]
+fn test_struct() {
+    let input = [
+        0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, // local big endian
+        42,
+        0xEF, 0xBE, 0xAD, 0xDE, // "dead beef"
+        0, 0, 0, // padding
+        0xDB, 0x0F, 0x49, 0x40, // PI
+        0x12, 0x34, 0x56, 0x78, // magic
+    ];
+    let decoded = Test::decode(&mut input.as_ref()).unwrap();
+    assert_eq!(decoded, EXAMPLE);
+    let mut output = Vec::new();
+    EXAMPLE.encode(&mut output).unwrap();
+    assert_eq!(output, input);
+}
+
+#[test]
+fn test_temporaries() {
+    let input = [
+        3, 0, 0, 0, // length
+        1, 2, 3, // xs
+        10, 0, 20, 0, 30, 0 // ys
+    ];
+    let expected = Values { values: Box::new([(1, 10), (2, 20), (3, 30)]) };
+    let decoded = Values::decode(&mut input.as_ref()).unwrap();
+    assert_eq!(decoded, expected);
+    let mut output = Vec::new();
+    expected.encode(&mut output).unwrap();
+    assert_eq!(output, input);
+}
+
+#[test]
+fn test_enum() {
+    let input = [0x10, 0xFF, 0xFE, 0x00, 0x02];
+    let decoded = Command::decode(&mut input.as_ref()).unwrap();
+    assert_eq!(decoded, Command::Move { x: -2, y: 2 });
+    let mut output = Vec::new();
+    decoded.encode(&mut output).unwrap();
+    assert_eq!(output, input);
+
+    let input = [0x20, b'S', b'T', b'O', b'P'];
+    let decoded = Command::decode(&mut input.as_ref()).unwrap();
+    assert_eq!(decoded, Command::Stop);
+    let mut output = Vec::new();
+    decoded.encode(&mut output).unwrap();
+    assert_eq!(output, input);
+
+    let decoded = Command::decode(&mut [0x21].as_ref()).unwrap();
+    assert_eq!(decoded, Command::Nop);
+}
+
+fn main() {}
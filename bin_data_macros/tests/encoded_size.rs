@@ -0,0 +1,101 @@
+use bin_data::context::{ArgsBuilderFinished, Context, Endian, NoEndian};
+use bin_data::data::{Encode, SliceView};
+use bin_data::stream::{dir, EncodeError, PositionTracker};
+use bin_data_macros::bin_data;
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Entry {
+        @magic(*b"EN"),
+        pub flags: u8,
+        #[bin_data(if = flags & 1 != 0)]
+        pub value: Option<u32>,
+        #[bin_data(encode = name.len() as u8)]
+        let name_length: u8,
+        #[bin_data(args:decode { count = name_length as usize })]
+        pub name: String,
+        @assert(*name_length <= 8, "name too long"),
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Table {
+        #[bin_data(encode = entries.len() as u16)]
+        let entry_count: u16,
+        @align(4),
+        #[bin_data(args:decode { count = entry_count as usize })]
+        pub entries: Vec<Entry>,
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Inner {
+        pub a: u8,
+        @align(4),
+        pub b: u8,
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Outer {
+        pub x: u8,
+        pub inner: Inner,
+        #[bin_data(args:decode { count = 2 })]
+        pub list: Vec<Inner>,
+    }
+}
+
+#[test]
+fn test_encoded_size() {
+    let first = Entry { flags: 1, value: Some(42), name: "peashooter".to_string() };
+    assert!(matches!(first.encoded_size(), Err(EncodeError::AssertionFailed { .. })));
+    let first = Entry { name: "pea".to_string(), ..first };
+    assert_eq!(first.encoded_size().unwrap(), 11);
+    let mut output = Vec::new();
+    first.encode(&mut output).unwrap();
+    assert_eq!(output.len(), 11);
+    let second = Entry { flags: 0, value: None, name: String::new() };
+    assert_eq!(second.encoded_size().unwrap(), 4);
+
+    let table = Table { entries: vec![first, second] };
+    assert_eq!(table.encoded_size().unwrap(), 19);
+    let mut output = Vec::new();
+    table.encode(&mut output).unwrap();
+    assert_eq!(output.len(), 19);
+
+    let names = ["a", "bc", "def"];
+    let lengths = SliceView::new(&names, |name| name.len() as u16);
+    let args = <Vec<u16> as Context<dir::Write>>::args_builder().finish();
+    assert_eq!(lengths.encoded_size_with(Endian::Little, args).unwrap(), 6);
+}
+
+#[test]
+fn test_nested_align() {
+    let inner = Inner { a: 1, b: 2 };
+    assert_eq!(inner.encoded_size().unwrap(), 5);
+    let outer = Outer { x: 0, inner: inner.clone(), list: vec![inner.clone(), inner] };
+    let mut output = Vec::new();
+    outer.encode(&mut output).unwrap();
+    // `inner` starts at 1 and the list elements at 5 and 9, so `b` is padded to 4, 8, and 12
+    assert_eq!(output, [0, 1, 0, 0, 2, 1, 0, 0, 2, 1, 0, 0, 2]);
+    assert_eq!(outer.encoded_size().unwrap(), 13);
+    let args = <Vec<Inner> as Context<dir::Write>>::args_builder().finish();
+    assert_eq!(outer.list.encoded_size_with(NoEndian, args).unwrap(), 9);
+}
+
+#[test]
+fn test_counter() {
+    let mut counter = PositionTracker::counter();
+    "wall-nut".encode_with(&mut counter, Default::default(), ()).unwrap();
+    42_u32.encode_with(&mut counter, Endian::Big, ()).unwrap();
+    assert_eq!(counter.position(), 12);
+}
+
+fn main() {}
@@ -0,0 +1,63 @@
+use std::error::Error;
+use std::io::Read;
+use bin_data::data::Decode;
+use bin_data::stream::DecodeError;
+use bin_data_macros::bin_data;
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "big")]
+    pub struct Save {
+        @magic(*b"SAVE"),
+        pub slot: u16,
+        #[bin_data(encode = name.len() as u8)]
+        let name_length: u8,
+        #[bin_data(args:decode { count = name_length as usize })]
+        pub name: String,
+        @magic(*b"END"),
+    }
+}
+
+/// Reader yielding one byte at a time, and implementing neither `Seek` nor `BufRead`.
+struct Trickle<'a>(&'a [u8]);
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.0.len().min(buf.len()).min(1);
+        buf[..n].copy_from_slice(&self.0[..n]);
+        self.0 = &self.0[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn test_magic_mismatch_offset() {
+    let input = b"SAVE\x00\x01\x03abcEDN";
+    let err = Save::decode(&mut Trickle(input)).unwrap_err();
+    assert_eq!(err.offset(), Some(10));
+    assert!(matches!(err.root_cause(), DecodeError::MagicMismatch { .. }));
+
+    let err = Save::decode(&mut b"SAVF".as_ref()).unwrap_err();
+    assert_eq!(err.offset(), Some(0));
+    assert_eq!(err.to_string(), "at byte offset 0");
+    assert_eq!(err.source().unwrap().to_string(), err.root_cause().to_string());
+}
+
+#[test]
+fn test_invalid_utf8_offset() {
+    let input = b"SAVE\x00\x01\x03a\xFFcEND";
+    let err = Save::decode(&mut Trickle(input)).unwrap_err();
+    assert_eq!(err.offset(), Some(7));
+    assert_eq!(err.path().unwrap().to_string(), "Save.name");
+    assert!(matches!(err.root_cause(), DecodeError::DecodeUtf8Error { .. }));
+}
+
+#[test]
+fn test_incomplete_offset() {
+    let input = b"SAVE\x00\x01\x05abc";
+    let err = Save::decode(&mut Trickle(input)).unwrap_err();
+    assert_eq!(err.offset(), Some(7));
+    assert!(matches!(err.root_cause(), DecodeError::IncompleteData(..)));
+}
+
+fn main() {}
@@ -0,0 +1,87 @@
+use std::error::Error;
+use bin_data::data::{Decode, Encode};
+use bin_data::stream::{DecodeError, EncodeError, PathSegment};
+use bin_data_macros::bin_data;
+
+bin_data! {
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Zombie {
+        #[bin_data(bits = 4)]
+        pub kind: u8,
+        #[bin_data(bits = 4)]
+        pub lane: u8,
+        pub delay: u16,
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Wave {
+        #[bin_data(encode = zombies.len() as u8)]
+        let zombie_count: u8,
+        #[bin_data(args:decode { count = zombie_count as usize })]
+        pub zombies: Vec<Zombie>,
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Level {
+        #[bin_data(encode = waves.len() as u8)]
+        let wave_count: u8,
+        #[bin_data(args:decode { count = wave_count as usize })]
+        pub waves: Vec<Wave>,
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    #[bin_data(tag: u8)]
+    pub enum Event {
+        #[bin_data(tag = 1)]
+        Spawn(Zombie),
+    }
+}
+
+#[test]
+fn test_decode_path() {
+    let input = [4, 0, 0, 0, 2, 0x21, 0x10];
+    let err = Level::decode(&mut input.as_ref()).unwrap_err();
+    assert_eq!(err.path().unwrap().to_string(), "Level.waves[3].zombies[0].delay");
+    assert_eq!(err.to_string(), "failed to decode 'Level.waves[3].zombies[0].delay' at byte offset 6");
+    assert_eq!(err.offset(), Some(6));
+    assert!(matches!(err.root_cause(), DecodeError::IncompleteData("u16", _)));
+    // every message in the chain is printed once
+    let source = err.source().unwrap();
+    assert_eq!(source.to_string(), err.root_cause().to_string());
+}
+
+#[test]
+fn test_encode_path() {
+    let zombie = Zombie { kind: 3, lane: 1, delay: 0 };
+    let mut level = Level { waves: vec![Wave { zombies: vec![zombie] }; 4] };
+    level.waves[3].zombies.push(Zombie { kind: 16, ..zombie });
+    let err = level.encode(&mut Vec::new()).unwrap_err();
+    assert_eq!(err.path().unwrap().to_string(), "Level.waves[3].zombies[1].kind");
+    assert!(matches!(err.root_cause(), EncodeError::InvalidData("kind")));
+    let segments = err.path().unwrap().segments().copied().collect::<Vec<_>>();
+    assert_eq!(segments, [
+        PathSegment::Field { type_name: "Level", field: "waves" },
+        PathSegment::Index(3),
+        PathSegment::Field { type_name: "Wave", field: "zombies" },
+        PathSegment::Index(1),
+        PathSegment::Field { type_name: "Zombie", field: "kind" },
+    ]);
+}
+
+#[test]
+fn test_variant_path() {
+    let err = Event::decode(&mut [1, 0x21].as_ref()).unwrap_err();
+    assert_eq!(err.path().unwrap().to_string(), "Event::Spawn.0.delay");
+}
+
+fn main() {}
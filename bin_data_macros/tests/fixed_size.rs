@@ -0,0 +1,77 @@
+use bin_data::data::{Be, Decode, Encode, FixedSize};
+use bin_data_macros::{bin_data, BinData};
+
+bin_data! {
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Position {
+        pub lane: u8,
+        @pad(1),
+        pub column: u16,
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Plant {
+        @magic(Be(0x504C_u16)),
+        #[bin_data(bits = 4)]
+        pub kind: u8,
+        #[bin_data(bits = 4)]
+        pub level: u8,
+        pub position: Position,
+        @assert(position.lane < 6, "invalid lane"),
+        #[bin_data(decode = None)]
+        pub note: Option<u8>,
+        #[bin_data(encode = 0)]
+        let _reserved: u32,
+    }
+}
+
+// Directives see fields by value when decoding and by reference when encoding.
+#[allow(clippy::clone_on_copy)]
+mod header {
+    use bin_data_macros::bin_data;
+    bin_data! {
+        #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+        #[bin_data(endian = "little")]
+        pub struct Header {
+            pub header_size: u8,
+            @pad(header_size.clone() as usize - 1),
+            pub x: u8,
+        }
+    }
+}
+
+use header::Header;
+
+#[derive(BinData, Debug, Copy, Clone, Eq, PartialEq)]
+#[bin_data(endian = "big")]
+pub struct Pair(pub u32, #[bin_data(pad_before = 2)] pub u32);
+
+const OFFSETS: [usize; 3] = [0, Plant::ENCODED_SIZE, 2 * Plant::ENCODED_SIZE];
+
+#[test]
+fn test_fixed_size() {
+    assert_eq!(Position::ENCODED_SIZE, 4);
+    assert_eq!(Plant::ENCODED_SIZE, 11);
+    assert_eq!(Pair::ENCODED_SIZE, 10);
+    assert_eq!(OFFSETS, [0, 11, 22]);
+
+    let plant = Plant { kind: 1, level: 2, position: Position { lane: 3, column: 9 }, note: None };
+    let mut buffer = [0; Plant::ENCODED_SIZE];
+    plant.encode(&mut buffer.as_mut_slice()).unwrap();
+    assert_eq!(buffer, [0x50, 0x4C, 0x21, 3, 0, 9, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn test_value_dependent_pad() {
+    let header = Header { header_size: 3, x: 7 };
+    let mut buffer = Vec::new();
+    header.encode(&mut buffer).unwrap();
+    assert_eq!(buffer, [3, 0, 0, 7]);
+    assert_eq!(Header::decode(&mut &buffer[..]).unwrap(), header);
+}
+
+fn main() {}
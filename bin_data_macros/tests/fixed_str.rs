@@ -0,0 +1,61 @@
+use bin_data::data::{Decode, Encode, FixedSize, FixedStr};
+use bin_data::stream::EncodeError;
+use bin_data_macros::bin_data;
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Record {
+        pub name: FixedStr<8>,
+        #[bin_data(args { padding = b' ' })]
+        pub author: FixedStr<6>,
+        pub score: u16,
+    }
+}
+
+#[test]
+fn test_fixed_str() {
+    assert_eq!(Record::ENCODED_SIZE, 16);
+    let input = b"cherry\0\0dave  \x10\x00";
+    let record = Record::decode(&mut input.as_ref()).unwrap();
+    assert_eq!(record, Record {
+        name: FixedStr("cherry".to_string()),
+        author: FixedStr("dave".to_string()),
+        score: 16,
+    });
+    let mut output = Vec::new();
+    record.encode(&mut output).unwrap();
+    assert_eq!(output, input);
+
+    let full = Record::decode(&mut b"squashes  zombie\0\0".as_ref()).unwrap();
+    assert_eq!(full.name.0, "squashes");
+    assert_eq!(full.author.0, "  zomb");
+}
+
+#[test]
+fn test_fixed_str_too_long() {
+    let record = Record {
+        name: FixedStr("jalapeno".to_string()),
+        author: FixedStr("crazy dave".to_string()),
+        score: 0,
+    };
+    let err = record.encode(&mut Vec::new()).unwrap_err();
+    assert_eq!(err.path().unwrap().to_string(), "Record.author");
+    assert!(matches!(err.root_cause(), EncodeError::InvalidArgument("FixedStr", _)));
+    assert!(record.encoded_size().is_err());
+}
+
+#[test]
+fn test_fixed_str_ends_with_padding() {
+    let record = Record {
+        name: FixedStr("peashoot".to_string()),
+        author: FixedStr("dave ".to_string()),
+        score: 0,
+    };
+    let err = record.encode(&mut Vec::new()).unwrap_err();
+    assert_eq!(err.path().unwrap().to_string(), "Record.author");
+    assert!(matches!(err.root_cause(), EncodeError::InvalidArgument("FixedStr", "string ends with padding")));
+    assert!(record.encoded_size().is_err());
+}
+
+fn main() {}
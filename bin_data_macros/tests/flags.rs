@@ -0,0 +1,66 @@
+use bin_data::context::UnknownBits;
+use bin_data::data::{Decode, Encode, FixedSize};
+use bin_data::stream::{DecodeError, EncodeError};
+use bin_data_macros::bin_data;
+
+bin_data::flags! {
+    pub struct Traits: u32 {
+        const SHIELDED = 1 << 0;
+        const FLYING = 1 << 1;
+        const AQUATIC = 1 << 8;
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "big")]
+    pub struct Zombie {
+        pub traits: Traits,
+        #[bin_data(args { unknown_bits = UnknownBits::Preserve })]
+        pub modded: Traits,
+        #[bin_data(args { unknown_bits = UnknownBits::Drop })]
+        pub lenient: Traits,
+    }
+}
+
+#[test]
+fn test_flags() {
+    let input = [0, 0, 1, 2, 0x80, 0, 0, 1, 0x80, 0, 0, 3];
+    let zombie = Zombie::decode(&mut input.as_ref()).unwrap();
+    assert_eq!(zombie, Zombie {
+        traits: Traits::AQUATIC | Traits::FLYING,
+        modded: Traits::from_bits_retain(0x8000_0001),
+        lenient: Traits::SHIELDED | Traits::FLYING,
+    });
+    assert_eq!(format!("{:?}", zombie.modded), "Traits(SHIELDED | 0x80000000)");
+    let mut output = Vec::new();
+    zombie.encode(&mut output).unwrap();
+    assert_eq!(output, [0, 0, 1, 2, 0x80, 0, 0, 1, 0, 0, 0, 3]);
+    assert_eq!(Zombie::ENCODED_SIZE, 12);
+
+    let mut traits = Traits::empty();
+    traits |= Traits::FLYING;
+    traits.insert(Traits::SHIELDED);
+    traits.remove(Traits::FLYING);
+    assert_eq!(traits, Traits::SHIELDED);
+    assert_eq!(Traits::ALL.bits(), 0x103);
+    assert_eq!(Traits::from_bits(0x104), None);
+    assert_eq!(Traits::from_bits_truncate(0x104), Traits::AQUATIC);
+}
+
+#[test]
+fn test_flags_reject() {
+    let err = Zombie::decode(&mut [0, 0, 0, 4].as_ref()).unwrap_err();
+    assert_eq!(err.path().unwrap().to_string(), "Zombie.traits");
+    assert!(matches!(err.root_cause(), DecodeError::InvalidData("Traits")));
+
+    let zombie = Zombie {
+        traits: Traits::from_bits_retain(4),
+        modded: Traits::empty(),
+        lenient: Traits::empty(),
+    };
+    let err = zombie.encode(&mut Vec::new()).unwrap_err();
+    assert!(matches!(err.root_cause(), EncodeError::InvalidData("Traits")));
+}
+
+fn main() {}
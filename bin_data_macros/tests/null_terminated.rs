@@ -0,0 +1,55 @@
+use std::ffi::CString;
+use bin_data::data::{Decode, Encode, NullTerminated};
+use bin_data::stream::{DecodeError, EncodeError};
+use bin_data_macros::bin_data;
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Texture {
+        pub id: u16,
+        #[bin_data(args { max_length = 8 })]
+        pub name: NullTerminated<String>,
+        pub path: CString,
+    }
+}
+
+#[test]
+fn test_null_terminated() {
+    let input = b"\x01\x00grass\0tex/grass.png\0";
+    let texture = Texture::decode(&mut input.as_ref()).unwrap();
+    assert_eq!(texture, Texture {
+        id: 1,
+        name: NullTerminated("grass".to_string()),
+        path: CString::new("tex/grass.png").unwrap(),
+    });
+    let mut output = Vec::new();
+    texture.encode(&mut output).unwrap();
+    assert_eq!(output, input);
+    assert_eq!(texture.encoded_size().unwrap(), input.len() as u64);
+}
+
+#[test]
+fn test_null_terminated_errors() {
+    let err = Texture::decode(&mut b"\x01\x00grass".as_ref()).unwrap_err();
+    assert_eq!(err.path().unwrap().to_string(), "Texture.name");
+    assert!(matches!(err.root_cause(), DecodeError::IncompleteData("NullTerminated", _)));
+
+    let err = Texture::decode(&mut b"\x01\x00sunflower\0\0".as_ref()).unwrap_err();
+    assert_eq!(err.offset(), Some(10));
+    assert!(matches!(err.root_cause(), DecodeError::InvalidData("NullTerminated")));
+
+    let texture = Texture {
+        id: 1,
+        name: NullTerminated("gra\0ss".to_string()),
+        path: CString::default(),
+    };
+    let err = texture.encode(&mut Vec::new()).unwrap_err();
+    assert!(matches!(err.root_cause(), EncodeError::InvalidData("NullTerminated")));
+
+    let texture = Texture { name: NullTerminated("sunflower".to_string()), ..texture };
+    let err = texture.encode(&mut Vec::new()).unwrap_err();
+    assert!(matches!(err.root_cause(), EncodeError::InvalidArgument("NullTerminated", _)));
+}
+
+fn main() {}
@@ -0,0 +1,77 @@
+use bin_data::data::{Decode, Encode, Tagged};
+use bin_data::stream::EncodeError;
+use bin_data_macros::{bin_data, BinData};
+
+bin_data! {
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    #[bin_data(tag: u32)]
+    pub enum ZombieKind {
+        Regular,
+        #[bin_data(unknown)]
+        Unknown(u32),
+        Conehead,
+        #[bin_data(tag = 4)]
+        Buckethead,
+    }
+}
+
+#[derive(BinData, Debug, Clone, Eq, PartialEq)]
+#[bin_data(endian = "big")]
+#[bin_data(tag: u16)]
+pub enum Plant {
+    #[bin_data(tag = 0x10)]
+    Peashooter { damage: u8 },
+    #[bin_data(unknown)]
+    Modded(u16),
+}
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "big")]
+    pub struct Level {
+        #[bin_data(encode = zombies.len() as u8)]
+        let zombie_count: u8,
+        #[bin_data(args:decode { count = zombie_count as usize })]
+        pub zombies: Vec<ZombieKind>,
+        pub plant: Plant,
+    }
+}
+
+#[test]
+fn test_open_enum() {
+    assert_eq!(ZombieKind::decode(&mut [1, 0, 0, 0].as_ref()).unwrap(), ZombieKind::Conehead);
+    assert_eq!(ZombieKind::decode(&mut [2, 0, 0, 0].as_ref()).unwrap(), ZombieKind::Unknown(2));
+    assert_eq!(ZombieKind::Unknown(42).tag(), 42);
+    assert_eq!(ZombieKind::Buckethead.tag(), 4);
+    assert_eq!(Plant::Modded(0x99).tag(), 0x99);
+}
+
+#[test]
+fn test_open_enum_roundtrip() {
+    let input = [3, 0, 0, 0, 0, 9, 0, 0, 0, 4, 0, 0, 0, 0x12, 0x34];
+    let level = Level::decode(&mut input.as_ref()).unwrap();
+    assert_eq!(level, Level {
+        zombies: vec![ZombieKind::Regular, ZombieKind::Unknown(9), ZombieKind::Buckethead],
+        plant: Plant::Modded(0x1234),
+    });
+    let mut output = Vec::new();
+    level.encode(&mut output).unwrap();
+    assert_eq!(output, input);
+
+    let plant = Plant::decode(&mut [0x00, 0x10, 7].as_ref()).unwrap();
+    assert_eq!(plant, Plant::Peashooter { damage: 7 });
+}
+
+#[test]
+fn test_unknown_with_known_tag() {
+    let err = ZombieKind::Unknown(1).encode(&mut Vec::new()).unwrap_err();
+    assert!(matches!(err, EncodeError::InvalidArgument("ZombieKind", _)));
+    let mut output = Vec::new();
+    let err = Plant::Modded(0x10).encode(&mut output).unwrap_err();
+    assert!(matches!(err, EncodeError::InvalidArgument("Plant", _)));
+    assert!(output.is_empty());
+    assert!(ZombieKind::Unknown(4).encoded_size().is_err());
+}
+
+fn main() {}
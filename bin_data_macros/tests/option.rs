@@ -0,0 +1,73 @@
+use bin_data::data::{Decode, Encode};
+use bin_data::stream::{DecodeError, EncodeError};
+use bin_data_macros::bin_data;
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Spawn {
+        #[bin_data(args { strict_flag })]
+        pub wave: Option<u16>,
+        #[bin_data(args { sentinel = 0xFFFF_FFFF })]
+        pub target: Option<u32>,
+        pub has_name: bool,
+        #[bin_data(args:decode { present = has_name, arg = bin_data::context::StrArgsBuilder::default().count(3).finish() })]
+        #[bin_data(args:encode { present = *has_name })]
+        pub name: Option<String>,
+    }
+}
+
+#[test]
+fn test_option() {
+    let input = b"\x01\x05\x00\xFF\xFF\xFF\xFF\x01imp";
+    let spawn = Spawn::decode(&mut input.as_ref()).unwrap();
+    assert_eq!(spawn, Spawn { wave: Some(5), target: None, has_name: true, name: Some("imp".to_string()) });
+    let mut output = Vec::new();
+    spawn.encode(&mut output).unwrap();
+    assert_eq!(output, input);
+    assert_eq!(spawn.encoded_size().unwrap(), input.len() as u64);
+
+    let input = b"\x00\x07\x00\x00\x00\x00";
+    let spawn = Spawn::decode(&mut input.as_ref()).unwrap();
+    assert_eq!(spawn, Spawn { wave: None, target: Some(7), has_name: false, name: None });
+    let mut output = Vec::new();
+    spawn.encode(&mut output).unwrap();
+    assert_eq!(output, input);
+}
+
+#[test]
+fn test_option_errors() {
+    let err = Spawn::decode(&mut [2].as_ref()).unwrap_err();
+    assert_eq!(err.path().unwrap().to_string(), "Spawn.wave.flag");
+    assert!(matches!(err.root_cause(), DecodeError::InvalidData("bool")));
+
+    let spawn = Spawn { wave: None, target: Some(u32::MAX), has_name: false, name: None };
+    let err = spawn.encode(&mut Vec::new()).unwrap_err();
+    assert_eq!(err.path().unwrap().to_string(), "Spawn.target");
+    assert!(matches!(err.root_cause(), EncodeError::InvalidData("Option")));
+
+    let spawn = Spawn { wave: None, target: None, has_name: false, name: Some("imp".to_string()) };
+    let err = spawn.encode(&mut Vec::new()).unwrap_err();
+    assert_eq!(err.path().unwrap().to_string(), "Spawn.name");
+    assert!(matches!(err.root_cause(), EncodeError::InvalidData("Option")));
+}
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Loot {
+        #[bin_data(args { flag })]
+        pub item: Option<u8>,
+    }
+}
+
+#[test]
+fn test_option_lenient_flag() {
+    let loot = Loot::decode(&mut [2, 9].as_ref()).unwrap();
+    assert_eq!(loot, Loot { item: Some(9) });
+    let mut output = Vec::new();
+    loot.encode(&mut output).unwrap();
+    assert_eq!(output, [1, 9]);
+}
+
+fn main() {}
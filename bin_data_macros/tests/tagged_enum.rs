@@ -0,0 +1,108 @@
+use bin_data::data::{Decode, Encode, Tagged};
+use bin_data::context::Endian;
+use bin_data::stream::{DecodeError, EncodeError};
+use bin_data_macros::bin_data;
+
+bin_data! {
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    #[bin_data(endian = "little")]
+    #[bin_data(tag: u8)]
+    pub enum Shape {
+        #[bin_data(tag = 1)]
+        Circle { radius: f32 },
+        #[bin_data(tag = 2)]
+        Rect {
+            width: u16,
+            @pad(1),
+            height: u16,
+        },
+        #[bin_data(tag = 5)]
+        Empty,
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "inherit")]
+    #[bin_data(args { tag: u16 })]
+    pub enum Payload {
+        Word { value: u16 },
+        Bytes { first: u8, second: u8 },
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "big")]
+    pub struct Message {
+        #[bin_data(encode = payload.tag())]
+        let kind: u16,
+        #[bin_data(args { tag = kind })]
+        pub payload: Payload,
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    #[bin_data(tag: u8)]
+    pub enum Color {
+        Red = 1,
+        Green,
+        Blue = 7,
+        Alpha,
+    }
+}
+
+#[test]
+fn test_inline_tag() {
+    let input = [2, 3, 0, 0xFF, 4, 0];
+    let decoded = Shape::decode(&mut input.as_ref()).unwrap();
+    assert_eq!(decoded, Shape::Rect { width: 3, height: 4 });
+    let mut output = Vec::new();
+    decoded.encode(&mut output).unwrap();
+    assert_eq!(output, [2, 3, 0, 0, 4, 0]);
+
+    let decoded = Shape::decode(&mut [5].as_ref()).unwrap();
+    assert_eq!(decoded, Shape::Empty);
+    let decoded = Shape::decode(&mut [1, 0xDB, 0x0F, 0x49, 0x40].as_ref()).unwrap();
+    assert_eq!(decoded, Shape::Circle { radius: std::f32::consts::PI });
+}
+
+#[test]
+fn test_external_tag() {
+    let input = [0, 1, 0x12, 0x34];
+    let decoded = Message::decode(&mut input.as_ref()).unwrap();
+    assert_eq!(decoded, Message { payload: Payload::Bytes { first: 0x12, second: 0x34 } });
+    let mut output = Vec::new();
+    decoded.encode(&mut output).unwrap();
+    assert_eq!(output, input);
+
+    let message = Message { payload: Payload::Word { value: 0x1234 } };
+    let mut output = Vec::new();
+    message.encode(&mut output).unwrap();
+    assert_eq!(output, [0, 0, 0x12, 0x34]);
+}
+
+#[test]
+fn test_tag_mismatch() {
+    let payload = Payload::Word { value: 42 };
+    let err = payload.encode_with(&mut Vec::new(), Endian::Little, PayloadArgs { tag: 1 }).unwrap_err();
+    assert!(matches!(err, EncodeError::InvalidArgument("Payload", _)));
+}
+
+#[test]
+fn test_c_like_enum() {
+    let colors = [Color::Red, Color::Green, Color::Blue, Color::Alpha];
+    assert_eq!(colors.map(|color| color.tag()), [1, 2, 7, 8]);
+    for color in colors {
+        let mut output = Vec::new();
+        color.encode(&mut output).unwrap();
+        assert_eq!(output, [color.tag()]);
+        assert_eq!(Color::decode(&mut output.as_slice()).unwrap(), color);
+    }
+    let err = Color::decode(&mut [3].as_ref()).unwrap_err();
+    assert!(matches!(err.root_cause(), DecodeError::InvalidData("Color")));
+}
+
+fn main() {}
@@ -0,0 +1,89 @@
+use bin_data::context::Encoding;
+use bin_data::data::{Decode, DecodeBorrowed, Encode};
+use bin_data::stream::{DecodeError, EncodeError};
+use bin_data_macros::bin_data;
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Label {
+        #[bin_data(encode = text.chars().count() as u8)]
+        let length: u8,
+        #[bin_data(args:decode { count = length as usize, encoding = Encoding::Windows1252 })]
+        #[bin_data(args:encode { encoding = Encoding::Windows1252 })]
+        pub text: String,
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Code {
+        #[bin_data(args:decode { count = 4, encoding = Encoding::Ascii })]
+        #[bin_data(args:encode { encoding = Encoding::Ascii })]
+        pub code: String,
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Clone)]
+    #[bin_data(endian = "little")]
+    pub struct Tag<'a> {
+        #[bin_data(args:decode { count = 3, encoding = Encoding::Latin1 })]
+        #[bin_data(args:encode { encoding = Encoding::Latin1 })]
+        pub tag: &'a str,
+    }
+}
+
+#[test]
+fn test_windows_1252() {
+    let input = b"\x07\x80 5 \xE9t\x81";
+    let label = Label::decode(&mut input.as_ref()).unwrap();
+    assert_eq!(label.text, "\u{20AC} 5 \u{E9}t\u{81}");
+    let mut output = Vec::new();
+    label.encode(&mut output).unwrap();
+    assert_eq!(output, input);
+    assert_eq!(label.encoded_size().unwrap(), 8);
+
+    let label = Label { text: "\u{80}".to_string() };
+    let err = label.encode(&mut Vec::new()).unwrap_err();
+    assert!(matches!(err.root_cause(), EncodeError::UnmappableCharacter {
+        character: '\u{80}',
+        encoding: Encoding::Windows1252,
+    }));
+}
+
+#[test]
+fn test_latin1() {
+    let mut output = Vec::new();
+    "na\u{EF}ve".encode_with(&mut output, Default::default(), Encoding::Latin1).unwrap();
+    assert_eq!(output, b"na\xEFve");
+    let err = "\u{20AC}".encoded_size_with(Default::default(), Encoding::Latin1).unwrap_err();
+    assert!(matches!(err, EncodeError::UnmappableCharacter { character: '\u{20AC}', encoding: Encoding::Latin1 }));
+
+    let tag = Tag::decode_borrowed(&mut b"abc".as_ref()).unwrap();
+    assert_eq!(tag.tag, "abc");
+    let err = Tag::decode_borrowed(&mut b"ab\xE9".as_ref()).unwrap_err();
+    assert!(matches!(err.root_cause(), DecodeError::InvalidData("&str")));
+}
+
+#[test]
+fn test_long_text() {
+    let text = "caf\u{E9} ".repeat(30);
+    let mut output = Vec::new();
+    text.encode_with(&mut output, Default::default(), Encoding::Latin1).unwrap();
+    assert_eq!(output, b"caf\xE9 ".repeat(30));
+    assert_eq!(text.encoded_size_with(Default::default(), Encoding::Latin1).unwrap(), 150);
+}
+
+#[test]
+fn test_ascii() {
+    let code = Code::decode(&mut b"PVZ1".as_ref()).unwrap();
+    assert_eq!(code.code, "PVZ1");
+    let err = Code::decode(&mut b"PV\xDA1".as_ref()).unwrap_err();
+    assert!(matches!(err.root_cause(), DecodeError::InvalidData("String")));
+    let err = Code { code: "PV\u{DA}1".to_string() }.encode(&mut Vec::new()).unwrap_err();
+    assert!(matches!(err.root_cause(), EncodeError::UnmappableCharacter { character: '\u{DA}', encoding: Encoding::Ascii }));
+}
+
+fn main() {}
@@ -0,0 +1,47 @@
+use bin_data::data::{Decode, Encode, FixedSize, Le};
+use bin_data::stream::{DecodeError, EncodeError};
+use bin_data_macros::bin_data;
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "big")]
+    pub struct Path {
+        #[bin_data(encode = points.len() as u8)]
+        let point_count: u8,
+        #[bin_data(args:decode { count = point_count as usize })]
+        pub points: Vec<(u16, u16)>,
+        #[bin_data(encode = label.1.len() as u8)]
+        let label_length: u8,
+        #[bin_data(args:decode { args = ((), bin_data::context::StrArgsBuilder::default().count(label_length as usize).finish()) })]
+        pub label: (Le<u16>, String),
+    }
+}
+
+type Wide = (u8, u16, u32, u64, i8, i16, i32, i64, u8, u16, u32, u64);
+
+#[test]
+fn test_tuple() {
+    let input = b"\x02\x00\x01\x00\x02\x00\x03\x00\x04\x03\x07\x00red";
+    let path = Path::decode(&mut input.as_ref()).unwrap();
+    assert_eq!(path, Path { points: vec![(1, 2), (3, 4)], label: (Le(7), "red".to_string()) });
+    let mut output = Vec::new();
+    path.encode(&mut output).unwrap();
+    assert_eq!(output, input);
+    assert_eq!(path.encoded_size().unwrap(), input.len() as u64);
+    assert_eq!(<(u8, (u16, u32))>::ENCODED_SIZE, 7);
+    assert_eq!(Wide::ENCODED_SIZE, 45);
+}
+
+#[test]
+fn test_tuple_errors() {
+    let err = Path::decode(&mut b"\x02\x00\x01\x00\x02\x00\x03".as_ref()).unwrap_err();
+    assert_eq!(err.path().unwrap().to_string(), "Path.points[1].1");
+    assert!(matches!(err.root_cause(), DecodeError::IncompleteData("u16", _)));
+
+    let path = Path { points: vec![(u16::MAX, 0); 256], label: (Le(0), String::new()) };
+    let err = path.encode(&mut [0; 4].as_mut()).unwrap_err();
+    assert_eq!(err.path().unwrap().to_string(), "Path.points[0].1");
+    assert!(matches!(err.root_cause(), EncodeError::Io(_)));
+}
+
+fn main() {}
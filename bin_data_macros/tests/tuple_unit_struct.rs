@@ -0,0 +1,105 @@
+use bin_data::data::{Decode, Encode};
+use bin_data_macros::{bin_data, BinData};
+
+bin_data! {
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct PlantId(pub u32);
+}
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "big")]
+    pub struct Record(
+        @magic(*b"REC"),
+        pub PlantId,
+        #[bin_data(encode = _1.len() as u16)]
+        let length: u16,
+        @pad(1),
+        #[bin_data(args:decode { count = length as usize })]
+        pub Vec<u8>,
+    );
+}
+
+bin_data! {
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    pub struct Marker {
+        @magic(*b"MARK"),
+        @pad(2),
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    pub struct Nothing;
+}
+
+bin_data! {
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    #[bin_data(tag: u8)]
+    pub enum Value {
+        Byte(u8),
+        Pair(u16, #[bin_data(endian = "big")] u16),
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, BinData)]
+#[bin_data(endian = "little")]
+pub struct DerivedPair(#[bin_data(pad_after = 1)] u8, i16);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, BinData)]
+#[bin_data(magic_before = *b"UNIT")]
+pub struct DerivedUnit;
+
+#[test]
+fn test_tuple_struct() {
+    let input = [b'R', b'E', b'C', 1, 0, 0, 0, 0, 2, 0, 10, 20];
+    let decoded = Record::decode(&mut input.as_ref()).unwrap();
+    assert_eq!(decoded, Record(PlantId(1), vec![10, 20]));
+    let mut output = Vec::new();
+    decoded.encode(&mut output).unwrap();
+    assert_eq!(output, input);
+}
+
+#[test]
+fn test_unit_struct() {
+    let input = *b"MARK\0\0";
+    assert_eq!(Marker::decode(&mut input.as_ref()).unwrap(), Marker);
+    let mut output = Vec::new();
+    Marker.encode(&mut output).unwrap();
+    assert_eq!(output, input);
+
+    assert_eq!(Nothing::decode(&mut [].as_ref()).unwrap(), Nothing);
+    let mut output = Vec::new();
+    Nothing.encode(&mut output).unwrap();
+    assert!(output.is_empty());
+}
+
+#[test]
+fn test_tuple_variant() {
+    let input = [1, 0x34, 0x12, 0x12, 0x34];
+    let decoded = Value::decode(&mut input.as_ref()).unwrap();
+    assert_eq!(decoded, Value::Pair(0x1234, 0x1234));
+    let mut output = Vec::new();
+    decoded.encode(&mut output).unwrap();
+    assert_eq!(output, input);
+    assert_eq!(Value::decode(&mut [0, 42].as_ref()).unwrap(), Value::Byte(42));
+}
+
+#[test]
+fn test_derive() {
+    let input = [7, 0, 0xFE, 0xFF];
+    let decoded = DerivedPair::decode(&mut input.as_ref()).unwrap();
+    assert_eq!(decoded, DerivedPair(7, -2));
+    let mut output = Vec::new();
+    decoded.encode(&mut output).unwrap();
+    assert_eq!(output, input);
+
+    assert_eq!(DerivedUnit::decode(&mut b"UNIT".as_ref()).unwrap(), DerivedUnit);
+    let mut output = Vec::new();
+    DerivedUnit.encode(&mut output).unwrap();
+    assert_eq!(output, b"UNIT");
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use bin_data_macros::bin_data;
+
+bin_data! {
+    #[bin_data(endian = "little")]
+    pub struct Zombie {
+        pub flags: u8,
+        #[bin_data(if = *flags != 0)]
+        pub health: u16,
+    }
+}
+
+fn main() {}
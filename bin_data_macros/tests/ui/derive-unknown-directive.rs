@@ -0,0 +1,11 @@
+use bin_data_macros::BinData;
+
+#[derive(BinData)]
+#[bin_data(endian = "little")]
+pub struct Header {
+    pub version: u8,
+    #[bin_data(pad_befor = 3)]
+    pub size: u32,
+}
+
+fn main() {}
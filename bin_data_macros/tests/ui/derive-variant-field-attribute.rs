@@ -0,0 +1,14 @@
+use bin_data_macros::BinData;
+
+#[derive(BinData)]
+#[bin_data(endian = "little")]
+#[bin_data(tag: u8)]
+pub enum Shape {
+    #[bin_data(tag = 1)]
+    Point,
+    #[bin_data(tag = 2)]
+    #[bin_data(if = true)]
+    Circle(u32),
+}
+
+fn main() {}
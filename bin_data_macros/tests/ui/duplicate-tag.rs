@@ -0,0 +1,15 @@
+use bin_data_macros::bin_data;
+
+bin_data! {
+    #[bin_data(endian = "little")]
+    #[bin_data(tag: u8)]
+    pub enum DuplicateTag {
+        #[bin_data(tag = 1)]
+        First,
+        Second,
+        #[bin_data(tag = 2)]
+        Third,
+    }
+}
+
+fn main() {}
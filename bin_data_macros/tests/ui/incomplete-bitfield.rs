@@ -0,0 +1,26 @@
+use bin_data_macros::bin_data;
+
+bin_data! {
+    #[bin_data(endian = "little")]
+    pub struct Header {
+        #[bin_data(bits = 3)]
+        pub kind: u8,
+        #[bin_data(bits = 4)]
+        pub level: u8,
+        pub trailer: u8,
+    }
+}
+
+bin_data! {
+    #[bin_data(endian = "big")]
+    pub struct Word {
+        #[bin_data(bits = 3)]
+        #[bin_data(container = u16)]
+        pub kind: u8,
+        #[bin_data(bits = 5)]
+        pub level: u8,
+        pub trailer: u8,
+    }
+}
+
+fn main() {}
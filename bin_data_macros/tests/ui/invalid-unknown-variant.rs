@@ -0,0 +1,16 @@
+use bin_data_macros::bin_data;
+
+bin_data! {
+    #[bin_data(endian = "little")]
+    #[bin_data(tag: u8)]
+    pub enum Kind {
+        Known,
+        #[bin_data(unknown)]
+        #[bin_data(tag = 3)]
+        Other { raw: u8 },
+        #[bin_data(unknown)]
+        Another(u8),
+    }
+}
+
+fn main() {}
@@ -0,0 +1,19 @@
+use bin_data_macros::bin_data;
+
+bin_data! {
+    #[bin_data(endian = "little")]
+    #[bin_data(args { version: u16 })]
+    pub struct Inner {
+        #[bin_data(encode = version)]
+        let stored_version: u16,
+    }
+}
+
+bin_data! {
+    #[bin_data(endian = "little")]
+    pub struct Outer {
+        pub inner: Inner,
+    }
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use bin_data_macros::bin_data;
+
+bin_data! {
+    #[bin_data(endian = "little")]
+    pub enum MissingTag {
+        First { value: u32 },
+        Second,
+    }
+}
+
+fn main() {}
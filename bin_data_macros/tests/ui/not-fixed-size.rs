@@ -0,0 +1,16 @@
+use bin_data::data::FixedSize;
+use bin_data_macros::bin_data;
+
+bin_data! {
+    #[bin_data(endian = "little")]
+    pub struct Named {
+        #[bin_data(encode = name.len() as u8)]
+        let name_length: u8,
+        #[bin_data(args:decode { count = name_length as usize })]
+        pub name: String,
+    }
+}
+
+fn main() {
+    let _buffer = [0_u8; Named::ENCODED_SIZE];
+}
@@ -0,0 +1,60 @@
+use bin_data::data::{Decode, Encode};
+use bin_data::stream::DecodeError;
+use bin_data_macros::bin_data;
+
+bin_data! {
+    #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Record {
+        pub time: u16,
+        pub event: u8,
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Replay {
+        @magic(*b"RPL"),
+        pub version: u8,
+        #[bin_data(args:decode { until_eof })]
+        pub records: Vec<Record>,
+    }
+}
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "big")]
+    pub struct Samples {
+        #[bin_data(args:decode { args = [(); 2], until_eof })]
+        pub head: Box<[u16]>,
+        #[bin_data(args:decode { until_eof })]
+        pub tail: Vec<u8>,
+    }
+}
+
+#[test]
+fn test_until_eof() {
+    let input = b"RPL\x01\x10\x00\x02\x20\x00\x03";
+    let replay = Replay::decode(&mut input.as_ref()).unwrap();
+    assert_eq!(replay.records, [Record { time: 16, event: 2 }, Record { time: 32, event: 3 }]);
+    let mut output = Vec::new();
+    replay.encode(&mut output).unwrap();
+    assert_eq!(output, input);
+
+    let replay = Replay::decode(&mut b"RPL\x01".as_ref()).unwrap();
+    assert!(replay.records.is_empty());
+
+    let samples = Samples::decode(&mut [0, 1, 0, 2, 3, 4, 5].as_ref()).unwrap();
+    assert_eq!(samples, Samples { head: Box::new([1, 2]), tail: vec![3, 4, 5] });
+}
+
+#[test]
+fn test_until_eof_incomplete() {
+    let err = Replay::decode(&mut b"RPL\x01\x10\x00\x02\x20\x00".as_ref()).unwrap_err();
+    assert_eq!(err.path().unwrap().to_string(), "Replay.records[1].event");
+    assert_eq!(err.offset(), Some(9));
+    assert!(matches!(err.root_cause(), DecodeError::IncompleteData("u8", _)));
+}
+
+fn main() {}
@@ -0,0 +1,47 @@
+use bin_data::data::{Decode, Encode, NullTerminated, Utf16String};
+use bin_data::stream::DecodeError;
+use bin_data_macros::bin_data;
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Label {
+        #[bin_data(encode = text.0.encode_utf16().count() as u8)]
+        let length: u8,
+        #[bin_data(args:decode { count = length as usize })]
+        pub text: Utf16String,
+        #[bin_data(endian = "big")]
+        #[bin_data(args { max_length = 4 })]
+        pub tag: NullTerminated<Utf16String>,
+    }
+}
+
+#[test]
+fn test_utf16() {
+    let label = Label {
+        text: Utf16String("h\u{e9}\u{1F33B}".to_string()),
+        tag: NullTerminated(Utf16String("ok".to_string())),
+    };
+    let mut output = Vec::new();
+    label.encode(&mut output).unwrap();
+    assert_eq!(output, [4, b'h', 0, 0xE9, 0, 0x3C, 0xD8, 0x3B, 0xDF, 0, b'o', 0, b'k', 0, 0]);
+    assert_eq!(label.encoded_size().unwrap(), output.len() as u64);
+    assert_eq!(Label::decode(&mut output.as_slice()).unwrap(), label);
+}
+
+#[test]
+fn test_unpaired_surrogate() {
+    let input = [2, b'a', 0, 0x3C, 0xD8, 0, 0];
+    let err = Label::decode(&mut input.as_ref()).unwrap_err();
+    assert_eq!(err.path().unwrap().to_string(), "Label.text");
+    assert!(matches!(
+        err.root_cause(),
+        DecodeError::DecodeUtf16Error { valid_prefix, unpaired_surrogate: 0xD83C } if &**valid_prefix == "a",
+    ));
+
+    let input = [0, 0, b'a', 0, b'b', 0, b'c', 0, b'd', 0, b'e', 0, 0];
+    let err = Label::decode(&mut input.as_ref()).unwrap_err();
+    assert!(matches!(err.root_cause(), DecodeError::InvalidData("NullTerminated")));
+}
+
+fn main() {}
@@ -0,0 +1,72 @@
+use bin_data::data::{Decode, Encode, Sleb128, Uleb128, Vlq, ZigZag};
+use bin_data::stream::DecodeError;
+use bin_data_macros::bin_data;
+
+bin_data! {
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[bin_data(endian = "little")]
+    pub struct Wave {
+        #[bin_data(encode = Uleb128(zombies.len() as u32))]
+        let zombie_count: Uleb128<u32>,
+        #[bin_data(args:decode { count = zombie_count.0 as usize })]
+        pub zombies: Vec<ZigZag<i16>>,
+        pub delay: Vlq<u32>,
+        pub offset: Sleb128<i64>,
+    }
+}
+
+#[test]
+fn test_varint_roundtrip() {
+    let wave = Wave {
+        zombies: vec![ZigZag(0), ZigZag(-1), ZigZag(1), ZigZag(i16::MIN)],
+        delay: Vlq(0x20_0000),
+        offset: Sleb128(-129),
+    };
+    let mut output = Vec::new();
+    wave.encode(&mut output).unwrap();
+    assert_eq!(output, [4, 0x00, 0x01, 0x02, 0xFF, 0xFF, 0x03, 0x81, 0x80, 0x80, 0x00, 0xFF, 0x7E]);
+    assert_eq!(wave.encoded_size().unwrap(), output.len() as u64);
+    assert_eq!(Wave::decode(&mut output.as_slice()).unwrap(), wave);
+}
+
+#[test]
+fn test_varint_limits() {
+    for value in [0, 1, 127, 128, 16383, 16384, u64::MAX] {
+        let mut output = Vec::new();
+        Uleb128(value).encode(&mut output).unwrap();
+        assert_eq!(Uleb128::<u64>::decode(&mut output.as_slice()).unwrap(), Uleb128(value));
+        assert_eq!(Uleb128(value).encoded_size().unwrap(), output.len() as u64);
+        let mut output = Vec::new();
+        Vlq(value).encode(&mut output).unwrap();
+        assert_eq!(Vlq::<u64>::decode(&mut output.as_slice()).unwrap(), Vlq(value));
+        assert_eq!(Vlq(value).encoded_size().unwrap(), output.len() as u64);
+    }
+    for value in [0, -1, 63, -64, 64, -65, i64::MIN, i64::MAX] {
+        let mut output = Vec::new();
+        Sleb128(value).encode(&mut output).unwrap();
+        assert_eq!(Sleb128::<i64>::decode(&mut output.as_slice()).unwrap(), Sleb128(value));
+        assert_eq!(Sleb128(value).encoded_size().unwrap(), output.len() as u64);
+        let mut output = Vec::new();
+        ZigZag(value).encode(&mut output).unwrap();
+        assert_eq!(ZigZag::<i64>::decode(&mut output.as_slice()).unwrap(), ZigZag(value));
+        assert_eq!(ZigZag(value).encoded_size().unwrap(), output.len() as u64);
+    }
+}
+
+#[test]
+fn test_varint_overflow() {
+    let err = Uleb128::<u8>::decode(&mut [0x80, 0x02].as_ref()).unwrap_err();
+    assert!(matches!(err.root_cause(), DecodeError::InvalidData("Uleb128")));
+    let err = Sleb128::<i8>::decode(&mut [0x80, 0x7E].as_ref()).unwrap_err();
+    assert!(matches!(err.root_cause(), DecodeError::InvalidData("Sleb128")));
+    let err = Vlq::<u64>::decode(&mut [0xFF; 11].as_ref()).unwrap_err();
+    assert!(matches!(err.root_cause(), DecodeError::InvalidData("Vlq")));
+    let err = ZigZag::<i32>::decode(&mut [0x80].as_ref()).unwrap_err();
+    assert!(matches!(err.root_cause(), DecodeError::IncompleteData("ZigZag", _)));
+
+    let err = Wave::decode(&mut [2, 0x00, 0x80, 0x80, 0x04].as_ref()).unwrap_err();
+    assert_eq!(err.path().unwrap().to_string(), "Wave.zombies[1]");
+    assert_eq!(err.offset(), Some(4));
+}
+
+fn main() {}